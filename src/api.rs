@@ -0,0 +1,209 @@
+//! The Jules API operations as a trait.
+//!
+//! [`JulesApi`] has one method for each endpoint of the Jules API and is
+//! implemented by [`JulesClient`]. Code written against the trait, either
+//! generically or through `dyn JulesApi`, can be tested with an in-memory
+//! fake such as `testing::FakeJules` (with the `mock` feature) instead of
+//! an HTTP server.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use jules_rs::api::JulesApi;
+//! use jules_rs::{JulesClient, Result, SessionState};
+//!
+//! /// Approves the plans of all sessions waiting for approval.
+//! async fn approve_waiting(api: &dyn JulesApi) -> Result<usize> {
+//!     let mut approved = 0;
+//!     for session in api.list_sessions(Some(100), None).await?.sessions {
+//!         if session.state == Some(SessionState::AwaitingPlanApproval) {
+//!             if let Some(name) = &session.name {
+//!                 api.approve_plan(name).await?;
+//!                 approved += 1;
+//!             }
+//!         }
+//!     }
+//!     Ok(approved)
+//! }
+//!
+//! # async fn example() -> Result<()> {
+//! let client = JulesClient::new("YOUR_API_KEY")?;
+//! approve_waiting(&client).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::JulesClient;
+use crate::error::Result;
+use crate::models::*;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// The operations of the Jules API.
+///
+/// The methods behave like the [`JulesClient`] methods of the same name.
+/// The trait is object safe, so implementations can be used as
+/// `&dyn JulesApi` or `Arc<dyn JulesApi>`.
+#[async_trait]
+pub trait JulesApi: Send + Sync {
+    /// Creates a new session. See [`JulesClient::create_session`].
+    async fn create_session(&self, session: &Session) -> Result<Session>;
+
+    /// Gets a session by its resource name. See [`JulesClient::get_session`].
+    async fn get_session(&self, name: &str) -> Result<Session>;
+
+    /// Deletes a session. See [`JulesClient::delete_session`].
+    async fn delete_session(&self, name: &str) -> Result<()>;
+
+    /// Lists sessions with pagination. See [`JulesClient::list_sessions`].
+    async fn list_sessions(
+        &self,
+        page_size: Option<i32>,
+        page_token: Option<String>,
+    ) -> Result<ListSessionsResponse>;
+
+    /// Sends a message to a session. See [`JulesClient::send_message`].
+    async fn send_message(&self, session_name: &str, prompt: &str) -> Result<()>;
+
+    /// Approves a session's plan. See [`JulesClient::approve_plan`].
+    async fn approve_plan(&self, session_name: &str) -> Result<()>;
+
+    /// Gets an activity by its resource name. See [`JulesClient::get_activity`].
+    async fn get_activity(&self, name: &str) -> Result<Activity>;
+
+    /// Lists a session's activities with pagination. See
+    /// [`JulesClient::list_activities`].
+    async fn list_activities(
+        &self,
+        session_name: &str,
+        page_size: Option<i32>,
+        page_token: Option<String>,
+    ) -> Result<ListActivitiesResponse>;
+
+    /// Gets a source by its resource name. See [`JulesClient::get_source`].
+    async fn get_source(&self, name: &str) -> Result<Source>;
+
+    /// Lists sources with pagination. See [`JulesClient::list_sources`].
+    async fn list_sources(
+        &self,
+        filter: Option<String>,
+        page_size: Option<i32>,
+        page_token: Option<String>,
+    ) -> Result<ListSourcesResponse>;
+}
+
+#[async_trait]
+impl JulesApi for JulesClient {
+    async fn create_session(&self, session: &Session) -> Result<Session> {
+        JulesClient::create_session(self, session).await
+    }
+
+    async fn get_session(&self, name: &str) -> Result<Session> {
+        JulesClient::get_session(self, name).await
+    }
+
+    async fn delete_session(&self, name: &str) -> Result<()> {
+        JulesClient::delete_session(self, name).await
+    }
+
+    async fn list_sessions(
+        &self,
+        page_size: Option<i32>,
+        page_token: Option<String>,
+    ) -> Result<ListSessionsResponse> {
+        JulesClient::list_sessions(self, page_size, page_token).await
+    }
+
+    async fn send_message(&self, session_name: &str, prompt: &str) -> Result<()> {
+        JulesClient::send_message(self, session_name, prompt).await
+    }
+
+    async fn approve_plan(&self, session_name: &str) -> Result<()> {
+        JulesClient::approve_plan(self, session_name).await
+    }
+
+    async fn get_activity(&self, name: &str) -> Result<Activity> {
+        JulesClient::get_activity(self, name).await
+    }
+
+    async fn list_activities(
+        &self,
+        session_name: &str,
+        page_size: Option<i32>,
+        page_token: Option<String>,
+    ) -> Result<ListActivitiesResponse> {
+        JulesClient::list_activities(self, session_name, page_size, page_token).await
+    }
+
+    async fn get_source(&self, name: &str) -> Result<Source> {
+        JulesClient::get_source(self, name).await
+    }
+
+    async fn list_sources(
+        &self,
+        filter: Option<String>,
+        page_size: Option<i32>,
+        page_token: Option<String>,
+    ) -> Result<ListSourcesResponse> {
+        JulesClient::list_sources(self, filter, page_size, page_token).await
+    }
+}
+
+#[async_trait]
+impl<T: JulesApi + ?Sized> JulesApi for Arc<T> {
+    async fn create_session(&self, session: &Session) -> Result<Session> {
+        (**self).create_session(session).await
+    }
+
+    async fn get_session(&self, name: &str) -> Result<Session> {
+        (**self).get_session(name).await
+    }
+
+    async fn delete_session(&self, name: &str) -> Result<()> {
+        (**self).delete_session(name).await
+    }
+
+    async fn list_sessions(
+        &self,
+        page_size: Option<i32>,
+        page_token: Option<String>,
+    ) -> Result<ListSessionsResponse> {
+        (**self).list_sessions(page_size, page_token).await
+    }
+
+    async fn send_message(&self, session_name: &str, prompt: &str) -> Result<()> {
+        (**self).send_message(session_name, prompt).await
+    }
+
+    async fn approve_plan(&self, session_name: &str) -> Result<()> {
+        (**self).approve_plan(session_name).await
+    }
+
+    async fn get_activity(&self, name: &str) -> Result<Activity> {
+        (**self).get_activity(name).await
+    }
+
+    async fn list_activities(
+        &self,
+        session_name: &str,
+        page_size: Option<i32>,
+        page_token: Option<String>,
+    ) -> Result<ListActivitiesResponse> {
+        (**self)
+            .list_activities(session_name, page_size, page_token)
+            .await
+    }
+
+    async fn get_source(&self, name: &str) -> Result<Source> {
+        (**self).get_source(name).await
+    }
+
+    async fn list_sources(
+        &self,
+        filter: Option<String>,
+        page_size: Option<i32>,
+        page_token: Option<String>,
+    ) -> Result<ListSourcesResponse> {
+        (**self).list_sources(filter, page_size, page_token).await
+    }
+}
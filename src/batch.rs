@@ -0,0 +1,635 @@
+//! Batch session creation from manifest files.
+//!
+//! A [`Manifest`] describes many sessions at once: a prompt template, default
+//! settings, and one entry per repository with its own branch and variables.
+//! [`JulesClient::create_sessions`] validates every entry, then creates the
+//! sessions with bounded concurrency and reports which session each entry
+//! produced.
+//!
+//! Manifests can be written in JSON, or in YAML or TOML with the `yaml` and
+//! `toml` features.
+//!
+//! # Manifest Format
+//!
+//! ```yaml
+//! prompt: "Migrate {{crate}} to the 2024 edition. Owner: {{team}}."
+//! title: "Edition 2024: {{crate}}"
+//! branch: main
+//! auto_pr: true
+//! variables:
+//!   team: platform
+//! entries:
+//!   - repo: acme/widgets
+//!     variables: { crate: widgets }
+//!   - id: gadgets-dev
+//!     source: sources/github/acme/gadgets
+//!     branch: develop
+//!     variables: { crate: gadgets }
+//! ```
+//!
+//! Prompts and titles are [templates](crate::template): placeholders are
+//! written `{{name}}` and are filled from the entry's `variables`, falling
+//! back to the manifest-wide `variables`. The built-in variables `branch` and
+//! `id` are always available, and `owner` and `repo` are available for
+//! entries on GitHub sources. Shared text can be included with `{{> name}}`
+//! from the library set with [`BatchOptions::templates`].
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use jules_rs::JulesClient;
+//! use jules_rs::batch::{BatchOptions, Manifest};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = JulesClient::new("TOKEN")?;
+//! let manifest = Manifest::from_path("refactor.json")?;
+//!
+//! let report = client
+//!     .create_sessions(&manifest, &BatchOptions::default().concurrency(8))
+//!     .await?;
+//! for result in &report.results {
+//!     println!("{} -> {:?}", result.id, result.session);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::JulesClient;
+use crate::error::{JulesError, Result};
+use crate::models::{AutomationMode, GitHubRepoContext, Session, Source, SourceContext};
+use crate::template::{PromptTemplate, TemplateLibrary, TemplateVars};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+/// The file format of a [`Manifest`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManifestFormat {
+    /// JSON.
+    Json,
+    /// YAML (requires the `yaml` feature).
+    Yaml,
+    /// TOML (requires the `toml` feature).
+    Toml,
+}
+
+impl ManifestFormat {
+    /// Guesses the format from a file extension (`json`, `yaml`, `yml`, `toml`).
+    pub fn from_extension(path: impl AsRef<Path>) -> Option<Self> {
+        let ext = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "json" => Some(Self::Json),
+            "yaml" | "yml" => Some(Self::Yaml),
+            "toml" => Some(Self::Toml),
+            _ => None,
+        }
+    }
+}
+
+/// A description of many sessions to create.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Manifest {
+    /// Prompt template shared by all entries.
+    pub prompt: String,
+    /// Optional title template shared by all entries.
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Default starting branch. Entries on GitHub sources default to the
+    /// repository's default branch when neither sets one.
+    #[serde(default)]
+    pub branch: Option<String>,
+    /// Whether plans require approval before the agent starts work.
+    #[serde(default)]
+    pub require_plan_approval: Option<bool>,
+    /// Whether to create a pull request automatically when changes are ready.
+    #[serde(default)]
+    pub auto_pr: bool,
+    /// Variables available to every entry.
+    #[serde(default)]
+    pub variables: BTreeMap<String, String>,
+    /// The sessions to create.
+    pub entries: Vec<ManifestEntry>,
+}
+
+/// A single session in a [`Manifest`].
+///
+/// Exactly one of `source` and `repo` must be set.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ManifestEntry {
+    /// Key identifying the entry in the report. Defaults to the repository
+    /// (`owner/repo`), the source name, or the entry's position.
+    #[serde(default)]
+    pub id: Option<String>,
+    /// Source resource name, e.g. `sources/github/acme/widgets`.
+    #[serde(default)]
+    pub source: Option<String>,
+    /// GitHub repository as `owner/repo`, resolved to a source.
+    #[serde(default)]
+    pub repo: Option<String>,
+    /// Starting branch, overriding the manifest default.
+    #[serde(default)]
+    pub branch: Option<String>,
+    /// Prompt template, overriding the manifest prompt.
+    #[serde(default)]
+    pub prompt: Option<String>,
+    /// Title template, overriding the manifest title.
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Variables for this entry, overriding manifest variables.
+    #[serde(default)]
+    pub variables: BTreeMap<String, String>,
+}
+
+impl Manifest {
+    /// Reads a manifest from a file, choosing the format by extension.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let format = ManifestFormat::from_extension(path).ok_or_else(|| {
+            JulesError::InvalidManifest(format!(
+                "cannot tell the format of {}; use a .json, .yaml, .yml or .toml extension",
+                path.display()
+            ))
+        })?;
+        Self::parse(&std::fs::read_to_string(path)?, format)
+    }
+
+    /// Parses a manifest from a string in the given format.
+    pub fn parse(text: &str, format: ManifestFormat) -> Result<Self> {
+        match format {
+            ManifestFormat::Json => serde_json::from_str(text)
+                .map_err(|e| JulesError::InvalidManifest(format!("invalid JSON: {e}"))),
+            #[cfg(feature = "yaml")]
+            ManifestFormat::Yaml => serde_yaml::from_str(text)
+                .map_err(|e| JulesError::InvalidManifest(format!("invalid YAML: {e}"))),
+            #[cfg(feature = "toml")]
+            ManifestFormat::Toml => toml::from_str(text)
+                .map_err(|e| JulesError::InvalidManifest(format!("invalid TOML: {e}"))),
+            #[allow(unreachable_patterns)]
+            other => Err(JulesError::InvalidManifest(format!(
+                "{other:?} manifests require the `{}` feature",
+                format!("{other:?}").to_lowercase()
+            ))),
+        }
+    }
+}
+
+/// Options for [`JulesClient::create_sessions`].
+#[derive(Debug, Clone)]
+pub struct BatchOptions {
+    concurrency: usize,
+    dry_run: bool,
+    templates: TemplateLibrary,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: 4,
+            dry_run: false,
+            templates: TemplateLibrary::new(),
+        }
+    }
+}
+
+impl BatchOptions {
+    /// Sets the maximum number of sessions created at the same time
+    /// (default 4).
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Validates the manifest and renders every session without creating any.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Sets the templates that prompts and titles may include with
+    /// `{{> name}}`.
+    pub fn templates(mut self, templates: TemplateLibrary) -> Self {
+        self.templates = templates;
+        self
+    }
+}
+
+/// The outcome of a batch, in manifest order.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct BatchReport {
+    /// One result per manifest entry.
+    pub results: Vec<BatchResult>,
+}
+
+impl BatchReport {
+    /// Returns the entries that failed.
+    pub fn failures(&self) -> impl Iterator<Item = &BatchResult> {
+        self.results.iter().filter(|r| r.error.is_some())
+    }
+}
+
+/// The outcome of a single manifest entry.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BatchResult {
+    /// The entry key.
+    pub id: String,
+    /// The source the session was created on.
+    pub source: String,
+    /// The starting branch.
+    pub branch: String,
+    /// The rendered prompt.
+    pub prompt: String,
+    /// The rendered title, if any.
+    pub title: Option<String>,
+    /// Resource name of the created session. `None` for dry runs and failures.
+    pub session: Option<String>,
+    /// URL of the created session in the Jules web app.
+    pub url: Option<String>,
+    /// Why the session could not be created.
+    pub error: Option<String>,
+}
+
+impl JulesClient {
+    /// Creates every session described by a manifest.
+    ///
+    /// All entries are resolved and rendered before any session is created,
+    /// so a missing variable, unknown repository, or unknown branch fails the
+    /// whole batch up front. Failures while creating individual sessions do
+    /// not stop the batch; they are recorded in the entry's
+    /// [`BatchResult::error`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JulesError::InvalidManifest`] if an entry is invalid, or an
+    /// API error if sources could not be listed.
+    pub async fn create_sessions(
+        &self,
+        manifest: &Manifest,
+        options: &BatchOptions,
+    ) -> Result<BatchReport> {
+        let planned = self.plan_batch(manifest, &options.templates).await?;
+        if options.dry_run {
+            return Ok(BatchReport {
+                results: planned.into_iter().map(|(result, _)| result).collect(),
+            });
+        }
+
+        let results = futures_util::stream::iter(planned)
+            .map(|(mut result, session)| async move {
+                match self.create_session(&session).await {
+                    Ok(created) => {
+                        result.session = created.name;
+                        result.url = created.url;
+                    }
+                    Err(e) => result.error = Some(e.to_string()),
+                }
+                result
+            })
+            .buffered(options.concurrency)
+            .collect()
+            .await;
+        Ok(BatchReport { results })
+    }
+
+    async fn plan_batch(
+        &self,
+        manifest: &Manifest,
+        templates: &TemplateLibrary,
+    ) -> Result<Vec<(BatchResult, Session)>> {
+        let mut repos: HashMap<String, Source> = HashMap::new();
+        let mut sources_by_name: HashMap<String, Source> = HashMap::new();
+        if !manifest.entries.is_empty() {
+            let mut sources = self.stream_sources();
+            while let Some(source) = sources.next().await {
+                let source = source?;
+                if let Some(gh) = &source.github_repo {
+                    let key = format!("{}/{}", gh.owner, gh.repo).to_lowercase();
+                    repos.insert(key, source.clone());
+                }
+                sources_by_name.insert(source.name.clone(), source);
+            }
+        }
+
+        let mut ids = HashSet::new();
+        let mut planned = Vec::with_capacity(manifest.entries.len());
+        for (index, entry) in manifest.entries.iter().enumerate() {
+            let invalid =
+                |msg: String| JulesError::InvalidManifest(format!("entry {index}: {msg}"));
+            let mut vars = manifest.variables.clone();
+
+            let (source, label) = match (&entry.source, &entry.repo) {
+                (Some(name), None) => {
+                    let source = sources_by_name
+                        .get(name)
+                        .ok_or_else(|| invalid(format!("source {name} does not exist")))?;
+                    (source, name)
+                }
+                (None, Some(repo)) => {
+                    let source = repos
+                        .get(&repo.to_lowercase())
+                        .ok_or_else(|| invalid(format!("{repo} is not connected to Jules")))?;
+                    (source, repo)
+                }
+                _ => return Err(invalid("set exactly one of `source` and `repo`".into())),
+            };
+            let branch = entry.branch.as_ref().or(manifest.branch.as_ref());
+            let branch = match &source.github_repo {
+                Some(gh) => {
+                    let branch = branch.unwrap_or(&gh.default_branch.display_name).clone();
+                    if !gh.has_branch(&branch) {
+                        return Err(invalid(format!(
+                            "branch `{branch}` does not exist on {label}"
+                        )));
+                    }
+                    vars.insert("owner".into(), gh.owner.clone());
+                    vars.insert("repo".into(), gh.repo.clone());
+                    branch
+                }
+                None => branch
+                    .ok_or_else(|| invalid(format!("no branch set for source {label}")))?
+                    .clone(),
+            };
+            let source = source.name.clone();
+
+            let id = entry
+                .id
+                .clone()
+                .or_else(|| entry.repo.clone())
+                .or_else(|| entry.source.clone())
+                .unwrap_or_else(|| index.to_string());
+            if !ids.insert(id.clone()) {
+                return Err(invalid(format!("duplicate id `{id}`")));
+            }
+            vars.insert("branch".into(), branch.clone());
+            vars.insert("id".into(), id.clone());
+            vars.extend(entry.variables.clone());
+
+            let vars: TemplateVars = vars.into_iter().collect();
+            let render = |name: &str, source: &str| {
+                PromptTemplate::new(name, source)
+                    .and_then(|t| templates.render_template(&t, &vars))
+                    .map_err(|e| match e {
+                        JulesError::Template(message) => invalid(message),
+                        e => e,
+                    })
+            };
+            let prompt = render("prompt", entry.prompt.as_ref().unwrap_or(&manifest.prompt))?;
+            let title = entry
+                .title
+                .as_ref()
+                .or(manifest.title.as_ref())
+                .map(|t| render("title", t))
+                .transpose()?;
+
+            let session = Session {
+                name: None,
+                id: None,
+                prompt: prompt.clone(),
+                source_context: SourceContext {
+                    source: source.clone(),
+                    github_repo_context: Some(GitHubRepoContext {
+                        starting_branch: branch.clone(),
+                    }),
+                },
+                title: title.clone(),
+                require_plan_approval: manifest.require_plan_approval,
+                automation_mode: manifest.auto_pr.then_some(AutomationMode::AutoCreatePr),
+                create_time: None,
+                update_time: None,
+                state: None,
+                url: None,
+                outputs: None,
+            };
+            let result = BatchResult {
+                id,
+                source,
+                branch,
+                prompt,
+                title,
+                session: None,
+                url: None,
+                error: None,
+            };
+            planned.push((result, session));
+        }
+        Ok(planned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::{HttpTransport, Request, Response};
+    use async_trait::async_trait;
+    use http::Method;
+    use serde_json::{Value, json};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    /// Serves two GitHub sources and creates sessions slowly, recording the
+    /// most requests in flight at once.
+    #[derive(Default)]
+    struct Fake {
+        created: AtomicUsize,
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+    }
+
+    fn source(repo: &str, branches: &[&str]) -> Value {
+        json!({
+            "name": format!("sources/github/acme/{repo}"),
+            "id": format!("github/acme/{repo}"),
+            "githubRepo": {
+                "owner": "acme",
+                "repo": repo,
+                "isPrivate": false,
+                "defaultBranch": { "displayName": "main" },
+                "branches": branches
+                    .iter()
+                    .map(|b| json!({ "displayName": b }))
+                    .collect::<Vec<_>>(),
+            },
+        })
+    }
+
+    #[async_trait]
+    impl HttpTransport for Fake {
+        async fn send(&self, request: Request) -> Result<Response> {
+            let body = if request.method() == Method::POST {
+                let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                self.in_flight.fetch_sub(1, Ordering::SeqCst);
+                let n = self.created.fetch_add(1, Ordering::SeqCst);
+                let mut session: Value = serde_json::from_slice(request.body())?;
+                session["name"] = json!(format!("sessions/{n}"));
+                session
+            } else {
+                json!({ "sources": [
+                    source("widgets", &["main", "develop"]),
+                    source("gadgets", &["main"]),
+                ] })
+            };
+            Ok(Response::new(serde_json::to_vec(&body)?))
+        }
+    }
+
+    fn client() -> (JulesClient, Arc<Fake>) {
+        let fake = Arc::new(Fake::default());
+        let client = JulesClient::new("key")
+            .unwrap()
+            .with_transport(Arc::clone(&fake));
+        (client, fake)
+    }
+
+    fn manifest(entries: Value) -> Manifest {
+        serde_json::from_value(json!({ "prompt": "Fix {{repo}}", "entries": entries })).unwrap()
+    }
+
+    async fn plan_error(manifest: &Manifest) -> String {
+        let (client, _) = client();
+        match client.plan_batch(manifest, &TemplateLibrary::new()).await {
+            Err(JulesError::InvalidManifest(message)) => message,
+            other => panic!("expected an invalid manifest, got {other:?}"),
+        }
+    }
+
+    #[cfg(all(feature = "yaml", feature = "toml"))]
+    #[test]
+    fn parses_every_format() {
+        let json = r#"{
+            "prompt": "Fix {{crate}}",
+            "branch": "main",
+            "auto_pr": true,
+            "variables": { "team": "platform" },
+            "entries": [
+                { "repo": "acme/widgets", "variables": { "crate": "widgets" } },
+                { "id": "gadgets", "source": "sources/github/acme/gadgets", "branch": "dev" }
+            ]
+        }"#;
+        let yaml = r#"
+prompt: "Fix {{crate}}"
+branch: main
+auto_pr: true
+variables:
+  team: platform
+entries:
+  - repo: acme/widgets
+    variables: { crate: widgets }
+  - id: gadgets
+    source: sources/github/acme/gadgets
+    branch: dev
+"#;
+        let toml = r#"
+prompt = "Fix {{crate}}"
+branch = "main"
+auto_pr = true
+variables = { team = "platform" }
+
+[[entries]]
+repo = "acme/widgets"
+variables = { crate = "widgets" }
+
+[[entries]]
+id = "gadgets"
+source = "sources/github/acme/gadgets"
+branch = "dev"
+"#;
+        let parsed = [
+            Manifest::parse(json, ManifestFormat::Json).unwrap(),
+            Manifest::parse(yaml, ManifestFormat::Yaml).unwrap(),
+            Manifest::parse(toml, ManifestFormat::Toml).unwrap(),
+        ]
+        .map(|m| serde_json::to_value(m).unwrap());
+        assert_eq!(parsed[0], parsed[1]);
+        assert_eq!(parsed[0], parsed[2]);
+        assert_eq!(parsed[0]["entries"][1]["branch"], "dev");
+    }
+
+    #[tokio::test]
+    async fn rejects_entries_with_both_or_neither_source_and_repo() {
+        let both = manifest(json!([
+            { "repo": "acme/widgets", "source": "sources/github/acme/widgets" },
+        ]));
+        assert!(plan_error(&both).await.contains("exactly one"));
+        let neither = manifest(json!([{ "id": "x" }]));
+        assert!(plan_error(&neither).await.contains("exactly one"));
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_branches_and_duplicate_ids() {
+        let branch = manifest(json!([{ "repo": "acme/gadgets", "branch": "develop" }]));
+        assert!(plan_error(&branch).await.contains("branch `develop`"));
+        let duplicate = manifest(json!([
+            { "repo": "acme/widgets" },
+            { "id": "acme/widgets", "repo": "acme/gadgets" },
+        ]));
+        let message = plan_error(&duplicate).await;
+        assert!(message.starts_with("entry 1:"), "{message}");
+        assert!(message.contains("duplicate id"), "{message}");
+    }
+
+    #[tokio::test]
+    async fn entry_variables_override_built_ins_and_manifest_variables() {
+        let manifest: Manifest = serde_json::from_value(json!({
+            "prompt": "{{team}} {{repo}} {{branch}} {{id}}",
+            "variables": { "team": "platform", "repo": "manifest", "id": "manifest" },
+            "entries": [
+                { "repo": "acme/widgets", "branch": "develop" },
+                { "id": "g", "repo": "acme/gadgets", "variables": { "team": "core", "branch": "entry" } },
+            ],
+        }))
+        .unwrap();
+        let (client, _) = client();
+        let planned = client
+            .plan_batch(&manifest, &TemplateLibrary::new())
+            .await
+            .unwrap();
+        let prompts: Vec<_> = planned.iter().map(|(r, _)| r.prompt.as_str()).collect();
+        assert_eq!(
+            prompts,
+            [
+                "platform widgets develop acme/widgets",
+                "core gadgets entry g"
+            ]
+        );
+        assert_eq!(
+            planned[1].1.source_context.source,
+            "sources/github/acme/gadgets"
+        );
+    }
+
+    #[tokio::test]
+    async fn dry_runs_report_every_entry_without_creating_sessions() {
+        let manifest = manifest(json!([{ "repo": "acme/widgets" }, { "repo": "acme/gadgets" }]));
+        let (client, fake) = client();
+        let report = client
+            .create_sessions(&manifest, &BatchOptions::default().dry_run(true))
+            .await
+            .unwrap();
+        assert_eq!(fake.created.load(Ordering::SeqCst), 0);
+        let ids: Vec<_> = report.results.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, ["acme/widgets", "acme/gadgets"]);
+        assert_eq!(report.results[1].prompt, "Fix gadgets");
+        assert!(report.results.iter().all(|r| r.session.is_none()));
+    }
+
+    #[tokio::test]
+    async fn creates_sessions_within_the_concurrency_limit() {
+        let entries: Vec<_> = (0..8)
+            .map(|i| json!({ "id": i.to_string(), "repo": "acme/widgets" }))
+            .collect();
+        let manifest = manifest(json!(entries));
+        let (client, fake) = client();
+        let report = client
+            .create_sessions(&manifest, &BatchOptions::default().concurrency(3))
+            .await
+            .unwrap();
+        assert_eq!(report.failures().count(), 0);
+        assert_eq!(fake.created.load(Ordering::SeqCst), 8);
+        assert!(report.results.iter().all(|r| r.session.is_some()));
+        let max = fake.max_in_flight.load(Ordering::SeqCst);
+        assert!((2..=3).contains(&max), "{max} requests in flight");
+    }
+}
@@ -0,0 +1,68 @@
+//! `jules batch`: create sessions from a manifest file.
+
+use crate::output::{self, Color, paint, print_table};
+use crate::{BatchArgs, CliResult};
+use jules_rs::JulesClient;
+use jules_rs::batch::{BatchOptions, Manifest};
+use jules_rs::template::TemplateLibrary;
+use std::process::ExitCode;
+
+pub async fn run(client: &JulesClient, args: BatchArgs, json: bool) -> CliResult<ExitCode> {
+    let manifest = Manifest::from_path(&args.manifest)?;
+    let mut options = BatchOptions::default()
+        .concurrency(args.concurrency)
+        .dry_run(args.dry_run);
+    if let Some(dir) = &args.templates {
+        options = options.templates(TemplateLibrary::from_dir(dir)?);
+    }
+    let report = client.create_sessions(&manifest, &options).await?;
+
+    if let Some(path) = &args.output {
+        std::fs::write(path, serde_json::to_string_pretty(&report)?)?;
+    }
+
+    if json {
+        output::print_json(&report)?;
+    } else if args.dry_run {
+        for result in &report.results {
+            println!(
+                "{} {} on {}",
+                paint(&result.id, Color::Bold),
+                result.source,
+                result.branch
+            );
+            if let Some(title) = &result.title {
+                println!("  title:  {title}");
+            }
+            println!("  prompt: {}", output::one_line(&result.prompt, 100));
+        }
+        eprintln!(
+            "{}",
+            paint(
+                format!("Dry run: {} session(s) validated", report.results.len()),
+                Color::Dim
+            )
+        );
+    } else {
+        let rows: Vec<[String; 3]> = report
+            .results
+            .iter()
+            .map(|r| {
+                let outcome = match (&r.session, &r.error) {
+                    (_, Some(e)) => paint(format!("error: {e}"), Color::Red),
+                    (Some(name), None) => name.clone(),
+                    (None, None) => "-".to_string(),
+                };
+                [r.id.clone(), r.branch.clone(), outcome]
+            })
+            .collect();
+        print_table(["ID", "BRANCH", "SESSION"], &rows);
+    }
+
+    let failed = report.failures().count();
+    if failed > 0 {
+        eprintln!("{failed} of {} session(s) failed", report.results.len());
+        return Ok(ExitCode::FAILURE);
+    }
+    Ok(ExitCode::SUCCESS)
+}
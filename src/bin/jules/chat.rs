@@ -0,0 +1,138 @@
+//! `jules chat`: converse with a session from the terminal.
+
+use crate::output::{self, Color, paint};
+use crate::resources::session_name;
+use crate::{ChatArgs, CliResult};
+use futures_util::StreamExt;
+use jules_rs::watch::WatchEvent;
+use jules_rs::{Activity, GitPatch, JulesClient, JulesError, Plan, SessionState};
+use std::collections::HashSet;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+const HELP: &str = "\
+Type a message and press Enter to send it to the agent.
+
+  /approve   approve the pending plan
+  /plan      show the latest plan
+  /diff      show the latest code changes
+  /status    show the session state
+  /help      show this help
+  /quit      leave the chat (Ctrl-D also works)";
+
+pub async fn run(client: &JulesClient, args: ChatArgs) -> CliResult {
+    let name = session_name(&args.session);
+    let interval = Duration::from_secs(args.interval);
+    let mut events = client.watch_session(&name, interval);
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut activities: Vec<Activity> = Vec::new();
+    // Kept across restarts of the watch, which reports everything again.
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut state: Option<SessionState> = None;
+
+    eprintln!(
+        "{}",
+        paint(
+            format!("Chatting with {name}. /help for commands."),
+            Color::Dim
+        )
+    );
+
+    loop {
+        tokio::select! {
+            event = events.next() => match event {
+                Some(Ok(WatchEvent::Activity(activity))) => {
+                    if seen.insert(activity.name.clone()) {
+                        output::print_activity(&activity, false);
+                        activities.push(*activity);
+                    }
+                }
+                Some(Ok(WatchEvent::StateChanged { to, .. })) => {
+                    if state.as_ref() != Some(&to) {
+                        println!("{} {}", paint("●", output::state_color(&to)), to.as_str());
+                        if to.is_awaiting_user() {
+                            eprintln!("{}", paint("The agent is waiting for you.", Color::Yellow));
+                        }
+                        state = Some(to);
+                    }
+                }
+                // A failed poll, e.g. a transient 503, ends the watch but not
+                // the chat: wait a poll interval and follow the session again.
+                Some(Err(e)) => {
+                    print_error(&e);
+                    tokio::time::sleep(interval).await;
+                    events = client.watch_session(&name, interval);
+                }
+                None => {
+                    eprintln!("{}", paint("Session finished.", Color::Dim));
+                    return Ok(());
+                }
+            },
+            line = lines.next_line() => {
+                let Some(line) = line? else {
+                    return Ok(());
+                };
+                let line = line.trim();
+                if line.is_empty() {
+                    continue;
+                }
+                match line.split_whitespace().next().unwrap_or_default() {
+                    "/quit" | "/exit" => return Ok(()),
+                    "/help" => println!("{HELP}"),
+                    "/approve" => match client.approve_plan(&name).await {
+                        Ok(()) => println!("{}", paint("Plan approval sent.", Color::Green)),
+                        Err(e) => print_error(&e),
+                    },
+                    "/plan" => match latest_plan(&activities) {
+                        Some(plan) => print_plan(plan),
+                        None => println!("No plan yet."),
+                    },
+                    "/diff" => match latest_patch(&activities) {
+                        Some(patch) => output::print_patch(patch),
+                        None => println!("No code changes yet."),
+                    },
+                    "/status" => match client.get_session(&name).await {
+                        Ok(session) => output::print_session(&session),
+                        Err(e) => print_error(&e),
+                    },
+                    cmd if cmd.starts_with('/') => {
+                        println!("Unknown command `{cmd}`. /help for commands.");
+                    }
+                    _ => {
+                        if let Err(e) = client.send_message(&name, line).await {
+                            print_error(&e);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Reports a failed command or poll without leaving the chat, e.g. a message
+/// sent while the session is not accepting input.
+fn print_error(error: &JulesError) {
+    eprintln!("{}", paint(format!("error: {error}"), Color::Red));
+}
+
+fn latest_plan(activities: &[Activity]) -> Option<&Plan> {
+    activities
+        .iter()
+        .rev()
+        .find_map(|a| a.plan_generated.as_ref().map(|p| &p.plan))
+}
+
+fn latest_patch(activities: &[Activity]) -> Option<&GitPatch> {
+    activities.iter().rev().find_map(|a| a.git_patches().last())
+}
+
+fn print_plan(plan: &Plan) {
+    let mut steps: Vec<_> = plan.steps.iter().collect();
+    steps.sort_by_key(|s| s.index);
+    for step in steps {
+        println!("{}. {}", step.index + 1, paint(&step.title, Color::Bold));
+        if !step.description.trim().is_empty() {
+            println!("   {}", step.description.trim());
+        }
+    }
+}
@@ -0,0 +1,50 @@
+//! Helpers for running `git` in the current directory.
+
+use crate::CliResult;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Runs `git` with the given arguments and returns its trimmed stdout.
+pub fn git(args: &[&str]) -> CliResult<String> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .map_err(|e| format!("failed to run git: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("git {}: {}", args.join(" "), stderr.trim()).into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Runs `git` with the given arguments, feeding `input` on stdin.
+pub fn git_with_input(args: &[&str], input: &str) -> CliResult<String> {
+    let mut child = Command::new("git")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to run git: {e}"))?;
+    child
+        .stdin
+        .take()
+        .ok_or("failed to open git stdin")?
+        .write_all(input.as_bytes())?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("git {}: {}", args.join(" "), stderr.trim()).into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Returns `true` if `git` succeeds with the given arguments.
+pub fn git_ok(args: &[&str]) -> bool {
+    Command::new("git")
+        .args(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|s| s.success())
+}
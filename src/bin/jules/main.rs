@@ -0,0 +1,284 @@
+//! `jules` - a command-line client for the Jules API.
+//!
+//! Built with the `cli` feature:
+//!
+//! ```text
+//! cargo install jules-rs --features cli
+//! export JULES_API_KEY=...
+//! jules sessions list
+//! ```
+
+mod batch;
+mod chat;
+mod git;
+mod new;
+mod output;
+mod pull;
+mod resources;
+mod tail;
+
+use clap::{Args, Parser, Subcommand};
+use jules_rs::{JulesClient, Secret};
+use std::process::ExitCode;
+
+type CliResult<T = ()> = Result<T, Box<dyn std::error::Error>>;
+
+/// Command-line client for the Jules API.
+#[derive(Debug, Parser)]
+#[command(name = "jules", version)]
+struct Cli {
+    /// API key from jules.google.com/settings.
+    #[arg(long, env = "JULES_API_KEY", hide_env_values = true, global = true)]
+    api_key: Option<Secret>,
+
+    /// Print JSON instead of human-readable output.
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Manage coding sessions.
+    #[command(subcommand, visible_alias = "session")]
+    Sessions(SessionsCommand),
+    /// Inspect session activities.
+    #[command(subcommand, visible_alias = "activity")]
+    Activities(ActivitiesCommand),
+    /// Inspect connected repositories.
+    #[command(subcommand, visible_alias = "source")]
+    Sources(SourcesCommand),
+    /// Follow a session's activities as they arrive.
+    ///
+    /// Exits with 0 when the session completes, 1 when it fails, and 2 when
+    /// `--until-idle` stops at a session that awaits user input.
+    Tail(TailArgs),
+    /// Create a session for the git repository in the current directory.
+    ///
+    /// The source is found by matching the remote's GitHub owner and
+    /// repository against the connected sources, and the session starts from
+    /// the current branch.
+    New(NewArgs),
+    /// Apply a session's final patch to the local repository.
+    Pull(PullArgs),
+    /// Create many sessions from a YAML, TOML or JSON manifest.
+    Batch(BatchArgs),
+    /// Converse with a session interactively.
+    Chat(ChatArgs),
+}
+
+#[derive(Debug, Subcommand)]
+enum SessionsCommand {
+    /// List sessions, in the order the API returns them.
+    List(ListSessionsArgs),
+    /// Show a session.
+    Get {
+        /// Session name (`sessions/123`) or ID.
+        session: String,
+    },
+    /// Create a session.
+    Create(CreateSessionArgs),
+    /// Delete a session.
+    Delete {
+        /// Session name (`sessions/123`) or ID.
+        session: String,
+    },
+    /// Send a message to a session.
+    Message {
+        /// Session name (`sessions/123`) or ID.
+        session: String,
+        /// The message to send.
+        prompt: String,
+    },
+    /// Approve the pending plan of a session.
+    Approve {
+        /// Session name (`sessions/123`) or ID.
+        session: String,
+    },
+}
+
+#[derive(Debug, Args)]
+struct ListSessionsArgs {
+    /// Maximum number of sessions to show.
+    #[arg(long, default_value_t = 30, conflicts_with = "all")]
+    limit: usize,
+    /// Show all sessions.
+    #[arg(long)]
+    all: bool,
+    /// Only show sessions in this state (e.g. `failed`, `in-progress`).
+    #[arg(long, value_parser = output::parse_state)]
+    state: Vec<jules_rs::SessionState>,
+    /// Only show sessions whose title or prompt contains this text.
+    #[arg(long)]
+    search: Option<String>,
+}
+
+#[derive(Debug, Args)]
+struct CreateSessionArgs {
+    /// The task for the agent.
+    prompt: String,
+    /// Source name (`sources/...`) of the repository to work on.
+    #[arg(long)]
+    source: String,
+    /// Branch to start from.
+    #[arg(long, default_value = "main")]
+    branch: String,
+    #[command(flatten)]
+    options: SessionOptions,
+}
+
+#[derive(Debug, Args)]
+struct SessionOptions {
+    /// Session title.
+    #[arg(long)]
+    title: Option<String>,
+    /// Require plan approval before the agent starts work.
+    #[arg(long)]
+    require_plan_approval: bool,
+    /// Automatically create a pull request when changes are ready.
+    #[arg(long)]
+    auto_pr: bool,
+}
+
+#[derive(Debug, Args)]
+struct NewArgs {
+    /// The task for the agent.
+    prompt: String,
+    /// Git remote that points at the GitHub repository.
+    #[arg(long, default_value = "origin")]
+    remote: String,
+    /// Branch to start from. Defaults to the current branch.
+    #[arg(long)]
+    branch: Option<String>,
+    #[command(flatten)]
+    options: SessionOptions,
+}
+
+#[derive(Debug, Args)]
+struct PullArgs {
+    /// Session name (`sessions/123`) or ID.
+    session: String,
+    /// Print the diff and check whether it applies, without changing anything.
+    #[arg(long)]
+    dry_run: bool,
+    /// Commit the changes using the suggested commit message. Refuses to run
+    /// if other changes are already staged.
+    #[arg(long)]
+    commit: bool,
+    /// Create and switch to a new branch at the patch's base commit first.
+    #[arg(long)]
+    branch: Option<String>,
+    /// Fall back to a three-way merge if the patch does not apply cleanly.
+    #[arg(long = "3way")]
+    three_way: bool,
+}
+
+#[derive(Debug, Args)]
+struct BatchArgs {
+    /// Path to the manifest (`.yaml`, `.yml`, `.toml` or `.json`).
+    manifest: std::path::PathBuf,
+    /// Maximum number of sessions created at the same time.
+    #[arg(long, default_value_t = 4)]
+    concurrency: usize,
+    /// Validate the manifest and show the sessions without creating them.
+    #[arg(long)]
+    dry_run: bool,
+    /// Directory of prompt templates that the manifest may include.
+    #[arg(long, value_name = "DIR")]
+    templates: Option<std::path::PathBuf>,
+    /// Write the results, mapping each entry to its session, to this JSON file.
+    #[arg(long, short)]
+    output: Option<std::path::PathBuf>,
+}
+
+#[derive(Debug, Args)]
+struct TailArgs {
+    /// Session name (`sessions/123`) or ID. Defaults to the most recently
+    /// created of the first 100 listed sessions.
+    session: Option<String>,
+    /// Seconds between polls.
+    #[arg(long, default_value_t = 5)]
+    interval: u64,
+    /// Print command output and progress details.
+    #[arg(long, short)]
+    output: bool,
+    /// Stop when the session awaits plan approval or user feedback.
+    #[arg(long)]
+    until_idle: bool,
+}
+
+#[derive(Debug, Args)]
+struct ChatArgs {
+    /// Session name (`sessions/123`) or ID.
+    session: String,
+    /// Seconds between polls for new activities.
+    #[arg(long, default_value_t = 3)]
+    interval: u64,
+}
+
+#[derive(Debug, Subcommand)]
+enum ActivitiesCommand {
+    /// List the activities of a session.
+    List {
+        /// Session name (`sessions/123`) or ID.
+        session: String,
+    },
+    /// Show an activity.
+    Get {
+        /// Activity name (`sessions/123/activities/456`), or an ID with
+        /// `--session`.
+        activity: String,
+        /// Session name or ID, for a bare activity ID.
+        #[arg(long)]
+        session: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum SourcesCommand {
+    /// List connected repositories.
+    List {
+        /// Filter expression passed to the API.
+        #[arg(long)]
+        filter: Option<String>,
+    },
+    /// Show a source.
+    Get {
+        /// Source name (`sources/...`).
+        source: String,
+    },
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli).await {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(cli: Cli) -> CliResult<ExitCode> {
+    let Some(api_key) = cli.api_key else {
+        return Err("no API key; pass --api-key or set JULES_API_KEY".into());
+    };
+    let client = JulesClient::new(api_key)?;
+    let json = cli.json;
+
+    match cli.command {
+        Command::Sessions(cmd) => resources::sessions(&client, cmd, json).await?,
+        Command::Activities(cmd) => resources::activities(&client, cmd, json).await?,
+        Command::Sources(cmd) => resources::sources(&client, cmd, json).await?,
+        Command::Tail(args) => return tail::run(&client, args, json).await,
+        Command::Chat(args) => chat::run(&client, args).await?,
+        Command::New(args) => new::run(&client, args, json).await?,
+        Command::Pull(args) => pull::run(&client, args).await?,
+        Command::Batch(args) => return batch::run(&client, args, json).await,
+    }
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,125 @@
+//! `jules new`: create a session for the current git repository.
+
+use crate::git::git;
+use crate::output::{Color, paint};
+use crate::resources::create_session;
+use crate::{CliResult, NewArgs};
+use jules_rs::JulesClient;
+
+pub async fn run(client: &JulesClient, args: NewArgs, json: bool) -> CliResult {
+    let url = git(&["remote", "get-url", &args.remote])?;
+    let (owner, repo) = parse_github_remote(&url).ok_or_else(|| {
+        format!(
+            "remote `{}` ({url}) is not a GitHub repository",
+            args.remote
+        )
+    })?;
+    let branch = match args.branch {
+        Some(branch) => branch,
+        None => current_branch()?,
+    };
+
+    let source = client
+        .find_github_source(&owner, &repo)
+        .await?
+        .ok_or_else(|| format!("{owner}/{repo} is not connected to Jules"))?;
+    let gh = source
+        .github_repo
+        .as_ref()
+        .ok_or_else(|| format!("{} is not a GitHub source", source.name))?;
+    if !gh.has_branch(&branch) {
+        return Err(format!(
+            "branch `{branch}` does not exist on {owner}/{repo}; push it first or pass --branch"
+        )
+        .into());
+    }
+
+    if !json {
+        eprintln!(
+            "{}",
+            paint(format!("Using {} on {branch}", source.name), Color::Dim)
+        );
+    }
+    create_session(client, args.prompt, source.name, branch, args.options, json).await
+}
+
+fn current_branch() -> CliResult<String> {
+    let branch = git(&["branch", "--show-current"])?;
+    if branch.is_empty() {
+        return Err("HEAD is detached; check out a branch or pass --branch".into());
+    }
+    Ok(branch)
+}
+
+/// Extracts `(owner, repo)` from a GitHub remote URL.
+///
+/// Handles `https://github.com/o/r(.git)`, `git@github.com:o/r(.git)` and
+/// `ssh://git@github.com/o/r(.git)`.
+fn parse_github_remote(url: &str) -> Option<(String, String)> {
+    let url = url.trim();
+    let path = if let Some(rest) = url.strip_prefix("git@github.com:") {
+        rest
+    } else {
+        let rest = url.split_once("://")?.1;
+        let (host, path) = rest.split_once('/')?;
+        let host = host.rsplit('@').next()?;
+        let host = host.split(':').next()?;
+        if !host.eq_ignore_ascii_case("github.com") {
+            return None;
+        }
+        path
+    };
+    let path = path.trim_end_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    let (owner, repo) = path.split_once('/')?;
+    if owner.is_empty() || repo.is_empty() || repo.contains('/') {
+        return None;
+    }
+    Some((owner.to_string(), repo.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn owner_repo() -> Option<(String, String)> {
+        Some(("o".to_string(), "r".to_string()))
+    }
+
+    #[test]
+    fn parses_https_remotes() {
+        assert_eq!(parse_github_remote("https://github.com/o/r"), owner_repo());
+        assert_eq!(
+            parse_github_remote("https://github.com/o/r.git"),
+            owner_repo()
+        );
+        assert_eq!(
+            parse_github_remote("https://user@github.com/o/r.git\n"),
+            owner_repo()
+        );
+    }
+
+    #[test]
+    fn parses_ssh_remotes() {
+        assert_eq!(parse_github_remote("git@github.com:o/r.git"), owner_repo());
+        assert_eq!(
+            parse_github_remote("ssh://git@github.com/o/r"),
+            owner_repo()
+        );
+        assert_eq!(
+            parse_github_remote("ssh://git@github.com:22/o/r.git"),
+            owner_repo()
+        );
+    }
+
+    #[test]
+    fn rejects_other_hosts_and_paths() {
+        assert_eq!(parse_github_remote("https://gitlab.com/o/r.git"), None);
+        assert_eq!(parse_github_remote("git@gitlab.com:o/r.git"), None);
+        assert_eq!(parse_github_remote("https://github.com/o"), None);
+        assert_eq!(
+            parse_github_remote("https://github.com/o/r/tree/main"),
+            None
+        );
+    }
+}
@@ -0,0 +1,307 @@
+//! Human-readable and JSON rendering for the CLI.
+
+use crate::CliResult;
+use chrono::{DateTime, Local, Utc};
+use jules_rs::{Activity, GitPatch, Session, SessionState, Source};
+use serde::Serialize;
+use std::io::IsTerminal;
+use std::sync::OnceLock;
+
+/// Prints a value as pretty-printed JSON.
+pub fn print_json<T: Serialize + ?Sized>(value: &T) -> CliResult {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+/// Prints sessions as JSON, keeping output-only fields.
+pub fn print_sessions_json(sessions: &[Session]) -> CliResult {
+    let values = sessions
+        .iter()
+        .map(Session::to_json_value)
+        .collect::<Result<Vec<_>, _>>()?;
+    print_json(&values)
+}
+
+/// Prints rows as a left-aligned table with a header.
+///
+/// The last column is not padded, so long titles do not produce trailing
+/// whitespace.
+pub fn print_table<const N: usize>(header: [&str; N], rows: &[[String; N]]) {
+    let mut widths = header.map(|h| h.chars().count());
+    for row in rows {
+        for (w, cell) in widths.iter_mut().zip(row) {
+            *w = (*w).max(cell.chars().count());
+        }
+    }
+    let line = |cells: &[String]| {
+        let mut out = String::new();
+        for (i, cell) in cells.iter().enumerate() {
+            if i + 1 == N {
+                out.push_str(cell);
+            } else {
+                out.push_str(&format!("{:<width$}  ", cell, width = widths[i]));
+            }
+        }
+        println!("{}", out.trim_end());
+    };
+    line(&header.map(str::to_string));
+    for row in rows {
+        line(row);
+    }
+}
+
+/// Formats a timestamp in the local time zone.
+pub fn time(t: Option<DateTime<Utc>>) -> String {
+    t.map(|t| t.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// Returns the state of a session as shown by the API.
+pub fn state(session: &Session) -> &'static str {
+    session.state.as_ref().map_or("-", SessionState::as_str)
+}
+
+/// Parses a session state such as `failed`, `in-progress` or `IN_PROGRESS`.
+pub fn parse_state(s: &str) -> Result<SessionState, String> {
+    let name = s.trim().to_uppercase().replace('-', "_");
+    serde_json::from_value(serde_json::Value::String(name))
+        .map_err(|_| format!("unknown session state `{s}`"))
+}
+
+/// Truncates text to a single line of at most `max` characters.
+pub fn one_line(text: &str, max: usize) -> String {
+    let line = text.lines().next().unwrap_or_default().trim();
+    if line.chars().count() > max || text.trim().lines().nth(1).is_some() {
+        let cut: String = line.chars().take(max.saturating_sub(1)).collect();
+        format!("{}…", cut.trim_end())
+    } else {
+        line.to_string()
+    }
+}
+
+/// Returns a one-line description of what an activity records.
+pub fn activity_summary(activity: &Activity) -> String {
+    if let Some(m) = &activity.agent_messaged {
+        return one_line(&m.agent_message, 80);
+    }
+    if let Some(m) = &activity.user_messaged {
+        return one_line(&m.user_message, 80);
+    }
+    if let Some(p) = &activity.plan_generated {
+        return format!("Plan generated ({} steps)", p.plan.steps.len());
+    }
+    if activity.plan_approved.is_some() {
+        return "Plan approved".to_string();
+    }
+    if let Some(p) = &activity.progress_updated {
+        return one_line(&p.title, 80);
+    }
+    if activity.session_completed.is_some() {
+        return "Session completed".to_string();
+    }
+    if let Some(f) = &activity.session_failed {
+        return format!("Session failed: {}", one_line(&f.reason, 60));
+    }
+    activity
+        .description
+        .as_deref()
+        .map(|d| one_line(d, 80))
+        .unwrap_or_default()
+}
+
+/// Returns `owner/repo` for a GitHub source, or `-`.
+pub fn repo(source: &Source) -> String {
+    source
+        .github_repo
+        .as_ref()
+        .map(|r| format!("{}/{}", r.owner, r.repo))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// Prints a session as `label: value` lines.
+pub fn print_session(session: &Session) {
+    let field = |label: &str, value: &str| println!("{:<10} {}", format!("{label}:"), value);
+    field("Name", session.name.as_deref().unwrap_or("-"));
+    field("Title", session.title.as_deref().unwrap_or("-"));
+    field("State", state(session));
+    field("Source", &session.source_context.source);
+    if let Some(ctx) = &session.source_context.github_repo_context {
+        field("Branch", &ctx.starting_branch);
+    }
+    field("Created", &time(session.create_time));
+    field("Updated", &time(session.update_time));
+    if let Some(url) = &session.url {
+        field("URL", url);
+    }
+    for pr in session.pull_requests() {
+        field("PR", &pr.url);
+    }
+    println!("\n{}", session.prompt.trim_end());
+}
+
+/// ANSI colours used for terminal output.
+#[derive(Debug, Clone, Copy)]
+pub enum Color {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    Dim,
+    Bold,
+}
+
+/// Wraps text in an ANSI colour when stdout is a terminal and `NO_COLOR` is unset.
+pub fn paint(text: impl std::fmt::Display, color: Color) -> String {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    let enabled = *ENABLED
+        .get_or_init(|| std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none());
+    if !enabled {
+        return text.to_string();
+    }
+    let code = match color {
+        Color::Red => "31",
+        Color::Green => "32",
+        Color::Yellow => "33",
+        Color::Blue => "34",
+        Color::Magenta => "35",
+        Color::Cyan => "36",
+        Color::Dim => "2",
+        Color::Bold => "1",
+    };
+    format!("\x1b[{code}m{text}\x1b[0m")
+}
+
+/// Returns the colour used for a session state.
+pub fn state_color(state: &SessionState) -> Color {
+    match state {
+        SessionState::Completed => Color::Green,
+        SessionState::Failed => Color::Red,
+        s if s.is_awaiting_user() => Color::Yellow,
+        _ => Color::Blue,
+    }
+}
+
+/// Prints an activity for a live view of a session.
+///
+/// Messages are coloured by author, plans are shown as checklists, commands
+/// with their exit codes, and code changes as per-file line counts. With
+/// `show_output`, command output is printed as well.
+pub fn print_activity(activity: &Activity, show_output: bool) {
+    let time = activity
+        .create_time
+        .with_timezone(&Local)
+        .format("%H:%M:%S");
+    let stamp = paint(time, Color::Dim);
+
+    if let Some(m) = &activity.agent_messaged {
+        println!("{stamp} {}", paint("agent:", Color::Cyan));
+        println!(
+            "{}",
+            indent(&paint(m.agent_message.trim_end(), Color::Cyan))
+        );
+    } else if let Some(m) = &activity.user_messaged {
+        println!("{stamp} {}", paint("you:", Color::Green));
+        println!(
+            "{}",
+            indent(&paint(m.user_message.trim_end(), Color::Green))
+        );
+    } else if let Some(p) = &activity.plan_generated {
+        println!("{stamp} {}", paint("Plan:", Color::Bold));
+        let mut steps: Vec<_> = p.plan.steps.iter().collect();
+        steps.sort_by_key(|s| s.index);
+        for step in steps {
+            println!("  [ ] {}. {}", step.index + 1, step.title);
+        }
+    } else if activity.plan_approved.is_some() {
+        println!("{stamp} {}", paint("✓ Plan approved", Color::Green));
+    } else if let Some(p) = &activity.progress_updated {
+        println!("{stamp} → {}", p.title.trim());
+        if show_output && !p.description.trim().is_empty() {
+            println!("{}", indent(&paint(p.description.trim_end(), Color::Dim)));
+        }
+    } else if activity.session_completed.is_some() {
+        println!("{stamp} {}", paint("✓ Session completed", Color::Green));
+    } else if let Some(f) = &activity.session_failed {
+        println!(
+            "{stamp} {}",
+            paint(format!("✗ Session failed: {}", f.reason), Color::Red)
+        );
+    }
+
+    for artifact in activity.artifacts.iter().flatten() {
+        if let Some(bash) = &artifact.bash_output {
+            let code = if bash.exit_code == 0 {
+                paint("exit 0", Color::Green)
+            } else {
+                paint(format!("exit {}", bash.exit_code), Color::Red)
+            };
+            println!(
+                "{stamp} {} {}  {code}",
+                paint("$", Color::Magenta),
+                bash.command
+            );
+            if show_output && !bash.output.trim().is_empty() {
+                println!("{}", indent(&paint(bash.output.trim_end(), Color::Dim)));
+            }
+        }
+        if let Some(patch) = artifact
+            .change_set
+            .as_ref()
+            .and_then(|c| c.git_patch.as_ref())
+        {
+            let stats = patch.diff_stats();
+            let (ins, del) = stats
+                .iter()
+                .fold((0, 0), |(i, d), s| (i + s.insertions, d + s.deletions));
+            println!(
+                "{stamp} {} {} file(s) changed, {} {}",
+                paint("±", Color::Yellow),
+                stats.len(),
+                paint(format!("+{ins}"), Color::Green),
+                paint(format!("-{del}"), Color::Red)
+            );
+            for stat in &stats {
+                println!(
+                    "    {} {} {}",
+                    stat.path,
+                    paint(format!("+{}", stat.insertions), Color::Green),
+                    paint(format!("-{}", stat.deletions), Color::Red)
+                );
+            }
+        }
+        if let Some(media) = &artifact.media {
+            println!(
+                "{stamp} {}",
+                paint(format!("[{}]", media.mime_type), Color::Dim)
+            );
+        }
+    }
+}
+
+/// Prints a patch with its suggested commit message and coloured diff lines.
+pub fn print_patch(patch: &GitPatch) {
+    if let Some(msg) = &patch.suggested_commit_message {
+        println!("{}\n", paint(msg.trim(), Color::Bold));
+    }
+    for line in patch.unidiff_patch.lines() {
+        let color = match line.as_bytes().first() {
+            Some(b'+') if !line.starts_with("+++") => Some(Color::Green),
+            Some(b'-') if !line.starts_with("---") => Some(Color::Red),
+            Some(b'@') => Some(Color::Cyan),
+            _ => None,
+        };
+        match color {
+            Some(color) => println!("{}", paint(line, color)),
+            None => println!("{line}"),
+        }
+    }
+}
+
+fn indent(text: &str) -> String {
+    text.lines()
+        .map(|l| format!("    {l}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
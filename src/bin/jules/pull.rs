@@ -0,0 +1,108 @@
+//! `jules pull`: apply a session's final patch to the local repository.
+
+use crate::git::{git, git_ok, git_with_input};
+use crate::output::{self, Color, paint};
+use crate::resources::session_name;
+use crate::{CliResult, PullArgs};
+use jules_rs::JulesClient;
+
+pub async fn run(client: &JulesClient, args: PullArgs) -> CliResult {
+    let name = session_name(&args.session);
+    let patch = client
+        .latest_patch(&name)
+        .await?
+        .ok_or_else(|| format!("{name} has not produced any code changes"))?;
+    let mut diff = patch.unidiff_patch.clone();
+    if !diff.ends_with('\n') {
+        diff.push('\n');
+    }
+
+    let base = patch.base_commit_id.trim();
+    let has_base = !base.is_empty() && git_ok(&["cat-file", "-e", &format!("{base}^{{commit}}")]);
+    if !has_base {
+        warn(&format!(
+            "base commit {} is not in the local repository; try `git fetch`",
+            short(base)
+        ));
+    } else if args.branch.is_none() {
+        let head = git(&["rev-parse", "HEAD"])?;
+        if head != base && !git_ok(&["merge-base", "--is-ancestor", base, "HEAD"]) {
+            warn(&format!(
+                "HEAD ({}) does not contain the patch base ({}); the patch may not apply",
+                short(&head),
+                short(base)
+            ));
+        } else if head != base {
+            let ahead = git(&["rev-list", "--count", &format!("{base}..HEAD")])?;
+            if ahead.parse::<u64>().is_ok_and(|n| n > 0) {
+                warn(&format!(
+                    "HEAD ({}) has moved on from the patch base ({}) by {ahead} commit(s)",
+                    short(&head),
+                    short(base)
+                ));
+            }
+        }
+    }
+
+    let mut apply = vec!["apply"];
+    if args.three_way {
+        apply.push("--3way");
+    } else if args.commit {
+        apply.push("--index");
+    }
+
+    if args.dry_run {
+        output::print_patch(&patch);
+        let mut check = apply.clone();
+        check.push("--check");
+        match git_with_input(&check, &diff) {
+            Ok(_) => eprintln!("{}", paint("The patch applies cleanly.", Color::Green)),
+            Err(e) => warn(&format!("the patch does not apply: {e}")),
+        }
+        return Ok(());
+    }
+
+    // `git commit` would also commit whatever was already staged.
+    if args.commit && !git_ok(&["diff", "--cached", "--quiet"]) {
+        return Err(
+            "the index has staged changes; commit or unstage them before using --commit".into(),
+        );
+    }
+
+    if let Some(branch) = &args.branch {
+        let start = if has_base { base } else { "HEAD" };
+        git(&["switch", "-c", branch, start])?;
+        eprintln!(
+            "{}",
+            paint(
+                format!("Switched to new branch {branch} at {}", short(start)),
+                Color::Dim
+            )
+        );
+    }
+
+    git_with_input(&apply, &diff)?;
+    let stats = patch.diff_stats();
+    eprintln!("Applied changes to {} file(s) from {name}", stats.len());
+
+    if args.commit {
+        let message = patch
+            .suggested_commit_message
+            .as_deref()
+            .map(str::trim)
+            .filter(|m| !m.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("Apply changes from {name}"));
+        git(&["commit", "-m", &message])?;
+        eprintln!("Committed: {}", message.lines().next().unwrap_or_default());
+    }
+    Ok(())
+}
+
+fn warn(message: &str) {
+    eprintln!("{} {message}", paint("warning:", Color::Yellow));
+}
+
+fn short(commit: &str) -> &str {
+    commit.get(..12).unwrap_or(commit)
+}
@@ -0,0 +1,277 @@
+//! Handlers for the `sessions`, `activities` and `sources` subcommands.
+
+use crate::output::{self, print_json, print_table};
+use crate::{ActivitiesCommand, CliResult, SessionOptions, SessionsCommand, SourcesCommand};
+use futures_util::StreamExt;
+use jules_rs::{
+    AutomationMode, GitHubRepoContext, JulesClient, Session, SessionQuery, SourceContext,
+};
+
+/// Expands a bare session ID into a full resource name.
+pub fn session_name(session: &str) -> String {
+    if session.starts_with("sessions/") {
+        session.to_string()
+    } else {
+        format!("sessions/{session}")
+    }
+}
+
+/// Expands an activity ID into a full resource name. A bare ID needs the
+/// session; `123/activities/456` is expanded like a bare session ID.
+pub fn activity_name(activity: &str, session: Option<&str>) -> CliResult<String> {
+    if activity.contains('/') {
+        return Ok(session_name(activity));
+    }
+    match session {
+        Some(session) => Ok(format!("{}/activities/{activity}", session_name(session))),
+        None => Err(format!("{activity} is not a full activity name; pass --session").into()),
+    }
+}
+
+/// Prints the name of a resource a command acted on, as JSON.
+fn print_name_json(name: &str) -> CliResult {
+    print_json(&serde_json::json!({ "name": name }))
+}
+
+pub async fn sessions(client: &JulesClient, cmd: SessionsCommand, json: bool) -> CliResult {
+    match cmd {
+        SessionsCommand::List(args) => {
+            let mut query = SessionQuery::new().states(args.state);
+            if let Some(text) = args.search {
+                query = query.text(text);
+            }
+            if !args.all {
+                query = query.limit(args.limit);
+            }
+            let sessions = query.run(client).await?;
+            if json {
+                return output::print_sessions_json(&sessions);
+            }
+            let rows: Vec<[String; 4]> = sessions
+                .iter()
+                .map(|s| {
+                    [
+                        s.name.clone().unwrap_or_default(),
+                        output::state(s).to_string(),
+                        output::time(s.create_time),
+                        output::one_line(s.title.as_deref().unwrap_or(&s.prompt), 60),
+                    ]
+                })
+                .collect();
+            print_table(["NAME", "STATE", "CREATED", "TITLE"], &rows);
+        }
+        SessionsCommand::Get { session } => {
+            let session = client.get_session(&session_name(&session)).await?;
+            if json {
+                return print_json(&session.to_json_value()?);
+            }
+            output::print_session(&session);
+        }
+        SessionsCommand::Create(args) => {
+            create_session(
+                client,
+                args.prompt,
+                args.source,
+                args.branch,
+                args.options,
+                json,
+            )
+            .await?
+        }
+        SessionsCommand::Delete { session } => {
+            let name = session_name(&session);
+            client.delete_session(&name).await?;
+            if json {
+                return print_name_json(&name);
+            }
+            println!("Deleted {name}");
+        }
+        SessionsCommand::Message { session, prompt } => {
+            let name = session_name(&session);
+            client.send_message(&name, &prompt).await?;
+            if json {
+                return print_name_json(&name);
+            }
+            println!("Sent message to {name}");
+        }
+        SessionsCommand::Approve { session } => {
+            let name = session_name(&session);
+            client.approve_plan(&name).await?;
+            if json {
+                return print_name_json(&name);
+            }
+            println!("Approved plan for {name}");
+        }
+    }
+    Ok(())
+}
+
+/// Creates a session and prints its name and URL.
+pub async fn create_session(
+    client: &JulesClient,
+    prompt: String,
+    source: String,
+    branch: String,
+    options: SessionOptions,
+    json: bool,
+) -> CliResult {
+    let session = Session {
+        name: None,
+        id: None,
+        prompt,
+        source_context: SourceContext {
+            source,
+            github_repo_context: Some(GitHubRepoContext {
+                starting_branch: branch,
+            }),
+        },
+        title: options.title,
+        require_plan_approval: Some(options.require_plan_approval),
+        automation_mode: options.auto_pr.then_some(AutomationMode::AutoCreatePr),
+        create_time: None,
+        update_time: None,
+        state: None,
+        url: None,
+        outputs: None,
+    };
+    let created = client.create_session(&session).await?;
+    if json {
+        return print_json(&created.to_json_value()?);
+    }
+    println!("Created {}", created.name.as_deref().unwrap_or("session"));
+    if let Some(url) = &created.url {
+        println!("{url}");
+    }
+    Ok(())
+}
+
+pub async fn activities(client: &JulesClient, cmd: ActivitiesCommand, json: bool) -> CliResult {
+    match cmd {
+        ActivitiesCommand::List { session } => {
+            let name = session_name(&session);
+            let activities = client
+                .stream_activities(&name)
+                .collect::<Vec<_>>()
+                .await
+                .into_iter()
+                .collect::<Result<Vec<_>, _>>()?;
+            if json {
+                return print_json(&activities);
+            }
+            let rows: Vec<[String; 4]> = activities
+                .iter()
+                .map(|a| {
+                    [
+                        a.id.clone(),
+                        output::time(Some(a.create_time)),
+                        a.originator.clone(),
+                        output::activity_summary(a),
+                    ]
+                })
+                .collect();
+            print_table(["ID", "TIME", "FROM", "SUMMARY"], &rows);
+        }
+        ActivitiesCommand::Get { activity, session } => {
+            let name = activity_name(&activity, session.as_deref())?;
+            let activity = client.get_activity(&name).await?;
+            if json {
+                return print_json(&activity);
+            }
+            println!("{:<10} {}", "Name:", activity.name);
+            println!(
+                "{:<10} {}",
+                "Time:",
+                output::time(Some(activity.create_time))
+            );
+            println!("{:<10} {}", "From:", activity.originator);
+            println!("{:<10} {}", "Summary:", output::activity_summary(&activity));
+            for artifact in activity.artifacts.iter().flatten() {
+                if let Some(bash) = &artifact.bash_output {
+                    println!("\n$ {}  (exit {})", bash.command, bash.exit_code);
+                    println!("{}", bash.output.trim_end());
+                }
+                if let Some(patch) = artifact
+                    .change_set
+                    .as_ref()
+                    .and_then(|c| c.git_patch.as_ref())
+                {
+                    println!("\n{}", patch.unidiff_patch.trim_end());
+                }
+                if let Some(media) = &artifact.media {
+                    println!(
+                        "\n[{} media, {} bytes base64]",
+                        media.mime_type,
+                        media.data.len()
+                    );
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+pub async fn sources(client: &JulesClient, cmd: SourcesCommand, json: bool) -> CliResult {
+    match cmd {
+        SourcesCommand::List { filter } => {
+            let sources = client
+                .stream_sources_filtered(filter)
+                .collect::<Vec<_>>()
+                .await
+                .into_iter()
+                .collect::<Result<Vec<_>, _>>()?;
+            if json {
+                return print_json(&sources);
+            }
+            let rows: Vec<[String; 4]> = sources
+                .iter()
+                .map(|s| {
+                    let gh = s.github_repo.as_ref();
+                    [
+                        s.name.clone(),
+                        output::repo(s),
+                        gh.map(|r| r.default_branch.display_name.clone())
+                            .unwrap_or_else(|| "-".to_string()),
+                        gh.map(|r| if r.is_private { "private" } else { "public" })
+                            .unwrap_or("-")
+                            .to_string(),
+                    ]
+                })
+                .collect();
+            print_table(["NAME", "REPO", "DEFAULT BRANCH", "VISIBILITY"], &rows);
+        }
+        SourcesCommand::Get { source } => {
+            let source = client.get_source(&source).await?;
+            if json {
+                return print_json(&source);
+            }
+            println!("{:<10} {}", "Name:", source.name);
+            println!("{:<10} {}", "Repo:", output::repo(&source));
+            if let Some(gh) = &source.github_repo {
+                println!("{:<10} {}", "Private:", gh.is_private);
+                println!("{:<10} {}", "Default:", gh.default_branch.display_name);
+                let branches: Vec<&str> = gh
+                    .branches
+                    .iter()
+                    .map(|b| b.display_name.as_str())
+                    .collect();
+                println!("{:<10} {}", "Branches:", branches.join(", "));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_activity_names() {
+        let full = "sessions/1/activities/2";
+        assert_eq!(activity_name(full, None).unwrap(), full);
+        assert_eq!(activity_name("1/activities/2", None).unwrap(), full);
+        assert_eq!(activity_name("2", Some("1")).unwrap(), full);
+        assert_eq!(activity_name("2", Some("sessions/1")).unwrap(), full);
+        assert!(activity_name("2", None).is_err());
+    }
+}
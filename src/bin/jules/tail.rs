@@ -0,0 +1,73 @@
+//! `jules tail`: follow a session live.
+
+use crate::output::{self, Color, paint};
+use crate::resources::session_name;
+use crate::{CliResult, TailArgs};
+use futures_util::StreamExt;
+use jules_rs::watch::WatchEvent;
+use jules_rs::{JulesClient, SessionState};
+use std::process::ExitCode;
+use std::time::Duration;
+
+/// Exit code when `--until-idle` stops at a session awaiting user input.
+const EXIT_AWAITING_USER: u8 = 2;
+
+/// Sessions searched for the most recent one; the API's maximum page size.
+const LATEST_PAGE_SIZE: i32 = 100;
+
+pub async fn run(client: &JulesClient, args: TailArgs, json: bool) -> CliResult<ExitCode> {
+    let name = match args.session {
+        Some(session) => session_name(&session),
+        None => latest_session(client).await?,
+    };
+    if !json {
+        eprintln!("{}", paint(format!("Following {name}"), Color::Dim));
+    }
+
+    let mut final_state = None;
+    let mut events = client.watch_session(&name, Duration::from_secs(args.interval));
+    while let Some(event) = events.next().await {
+        match event? {
+            WatchEvent::Activity(activity) => {
+                if json {
+                    let line = serde_json::json!({ "type": "activity", "activity": activity });
+                    println!("{line}");
+                } else {
+                    output::print_activity(&activity, args.output);
+                }
+            }
+            WatchEvent::StateChanged { from, to } => {
+                if json {
+                    let line =
+                        serde_json::json!({ "type": "stateChanged", "from": from, "to": to });
+                    println!("{line}");
+                } else {
+                    println!("{} {}", paint("●", output::state_color(&to)), to.as_str());
+                }
+                if args.until_idle && to.is_awaiting_user() {
+                    return Ok(ExitCode::from(EXIT_AWAITING_USER));
+                }
+                final_state = Some(to);
+            }
+        }
+    }
+
+    Ok(match final_state {
+        Some(SessionState::Completed) => ExitCode::SUCCESS,
+        _ => ExitCode::FAILURE,
+    })
+}
+
+/// Returns the name of the most recently created session on the first page
+/// of the listing. The API does not promise an order, so the page is searched
+/// by `create_time` rather than taking its first entry.
+pub async fn latest_session(client: &JulesClient) -> CliResult<String> {
+    client
+        .list_sessions(Some(LATEST_PAGE_SIZE), None)
+        .await?
+        .sessions
+        .into_iter()
+        .max_by_key(|s| s.create_time)
+        .and_then(|s| s.name)
+        .ok_or_else(|| "no sessions found".into())
+}
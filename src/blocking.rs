@@ -0,0 +1,363 @@
+//! A synchronous client for the Jules API.
+//!
+//! [`JulesClient`] mirrors [`crate::JulesClient`] with blocking methods, for
+//! build scripts and command-line tools without an async runtime. Paginated
+//! listings are returned as iterators ([`Iter`]) in place of the async
+//! streams. The client runs the async client on a private single-threaded
+//! runtime, so it must not be used from within an async runtime; use the
+//! async client there.
+//!
+//! This module is only available with the `blocking` feature.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use jules_rs::blocking::JulesClient;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = JulesClient::new("YOUR_API_KEY")?;
+//!
+//! for session in client.iter_sessions() {
+//!     let session = session?;
+//!     println!("{:?}: {:?}", session.title, session.state);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::batch::{BatchOptions, BatchReport, Manifest};
+use crate::chain::{ChainedSession, FollowUp};
+use crate::commands::CommandLog;
+use crate::error::Result;
+use crate::export::{ExportFormat, Transcript};
+use crate::models::*;
+use crate::secret::Secret;
+use crate::watch::WatchEvent;
+use futures_util::{Stream, StreamExt};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+/// A blocking client for the Jules API.
+///
+/// See the [module documentation](self) for an example.
+pub struct JulesClient {
+    inner: crate::JulesClient,
+    runtime: Runtime,
+}
+
+impl std::fmt::Debug for JulesClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JulesClient")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl JulesClient {
+    /// Creates a new blocking client. See [`crate::JulesClient::new`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JulesError::Io`](crate::JulesError::Io) if the runtime
+    /// cannot be started.
+    ///
+    /// # Panics
+    ///
+    /// The client panics if it is used or dropped within an async runtime,
+    /// because its private runtime cannot block or shut down there.
+    pub fn new(token: impl Into<Secret>) -> Result<Self> {
+        Self::from_async(crate::JulesClient::new(token)?)
+    }
+
+    /// Creates a blocking client for a different API endpoint. See
+    /// [`crate::JulesClient::with_base_url`].
+    pub fn with_base_url(token: impl Into<Secret>, base_url: &str) -> Result<Self> {
+        Self::from_async(crate::JulesClient::with_base_url(token, base_url)?)
+    }
+
+    /// Wraps an async client, keeping its middleware and metrics.
+    ///
+    /// # Panics
+    ///
+    /// The client panics if it is used or dropped within an async runtime,
+    /// because its private runtime cannot block or shut down there.
+    pub fn from_async(client: crate::JulesClient) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        Ok(Self {
+            inner: client,
+            runtime,
+        })
+    }
+
+    /// Returns the async client this client wraps.
+    pub fn as_async(&self) -> &crate::JulesClient {
+        &self.inner
+    }
+
+    fn iter<'a, T>(&'a self, stream: Pin<Box<dyn Stream<Item = Result<T>> + 'a>>) -> Iter<'a, T> {
+        Iter {
+            runtime: &self.runtime,
+            stream,
+        }
+    }
+
+    // --- Sessions API ---
+
+    /// Creates a new coding session. See [`crate::JulesClient::create_session`].
+    pub fn create_session(&self, session: &Session) -> Result<Session> {
+        self.runtime.block_on(self.inner.create_session(session))
+    }
+
+    /// Gets a session by its resource name.
+    pub fn get_session(&self, name: &str) -> Result<Session> {
+        self.runtime.block_on(self.inner.get_session(name))
+    }
+
+    /// Deletes a session.
+    pub fn delete_session(&self, name: &str) -> Result<()> {
+        self.runtime.block_on(self.inner.delete_session(name))
+    }
+
+    /// Lists one page of sessions. See [`crate::JulesClient::list_sessions`].
+    pub fn list_sessions(
+        &self,
+        page_size: Option<i32>,
+        page_token: Option<String>,
+    ) -> Result<ListSessionsResponse> {
+        self.runtime
+            .block_on(self.inner.list_sessions(page_size, page_token))
+    }
+
+    /// Returns an iterator over all sessions, fetching pages as needed. See
+    /// [`crate::JulesClient::stream_sessions`].
+    pub fn iter_sessions(&self) -> Iter<'_, Session> {
+        self.iter(self.inner.stream_sessions())
+    }
+
+    /// Sends a message to an active session.
+    pub fn send_message(&self, session_name: &str, prompt: &str) -> Result<()> {
+        self.runtime
+            .block_on(self.inner.send_message(session_name, prompt))
+    }
+
+    /// Approves the current plan for a session.
+    pub fn approve_plan(&self, session_name: &str) -> Result<()> {
+        self.runtime.block_on(self.inner.approve_plan(session_name))
+    }
+
+    /// Follows a session, yielding new activities and state changes until it
+    /// finishes. See [`crate::JulesClient::watch_session`].
+    pub fn watch_session<'a>(
+        &'a self,
+        session_name: &'a str,
+        poll_interval: Duration,
+    ) -> Iter<'a, WatchEvent> {
+        self.iter(self.inner.watch_session(session_name, poll_interval))
+    }
+
+    /// Starts a follow-up session on the branch of a session's pull request.
+    /// See [`crate::JulesClient::continue_from`].
+    pub fn continue_from(&self, parent: &Session, prompt: &str) -> Result<ChainedSession> {
+        self.runtime
+            .block_on(self.inner.continue_from(parent, prompt))
+    }
+
+    /// Starts a follow-up session with more options. See
+    /// [`crate::JulesClient::continue_from_with`].
+    pub fn continue_from_with(
+        &self,
+        parent: &Session,
+        follow_up: FollowUp,
+    ) -> Result<ChainedSession> {
+        self.runtime
+            .block_on(self.inner.continue_from_with(parent, follow_up))
+    }
+
+    /// Creates the sessions described by a manifest. See
+    /// [`crate::JulesClient::create_sessions`].
+    pub fn create_sessions(
+        &self,
+        manifest: &Manifest,
+        options: &BatchOptions,
+    ) -> Result<BatchReport> {
+        self.runtime
+            .block_on(self.inner.create_sessions(manifest, options))
+    }
+
+    // --- Activities API ---
+
+    /// Gets an activity by its resource name.
+    pub fn get_activity(&self, name: &str) -> Result<Activity> {
+        self.runtime.block_on(self.inner.get_activity(name))
+    }
+
+    /// Lists one page of a session's activities. See
+    /// [`crate::JulesClient::list_activities`].
+    pub fn list_activities(
+        &self,
+        session_name: &str,
+        page_size: Option<i32>,
+        page_token: Option<String>,
+    ) -> Result<ListActivitiesResponse> {
+        self.runtime.block_on(
+            self.inner
+                .list_activities(session_name, page_size, page_token),
+        )
+    }
+
+    /// Returns an iterator over all activities of a session. See
+    /// [`crate::JulesClient::stream_activities`].
+    pub fn iter_activities<'a>(&'a self, session_name: &'a str) -> Iter<'a, Activity> {
+        self.iter(self.inner.stream_activities(session_name))
+    }
+
+    /// Downloads every media artifact of a session into a directory. See
+    /// [`crate::JulesClient::download_session_media`].
+    pub fn download_session_media(
+        &self,
+        session_name: &str,
+        dir: impl AsRef<Path>,
+    ) -> Result<Vec<PathBuf>> {
+        self.runtime
+            .block_on(self.inner.download_session_media(session_name, dir))
+    }
+
+    /// Collects the commands a session ran. See
+    /// [`crate::JulesClient::session_commands`].
+    pub fn session_commands(&self, session_name: &str) -> Result<CommandLog> {
+        self.runtime
+            .block_on(self.inner.session_commands(session_name))
+    }
+
+    /// Fetches a session and its activities as a [`Transcript`].
+    pub fn transcript(&self, session_name: &str) -> Result<Transcript> {
+        self.runtime.block_on(self.inner.transcript(session_name))
+    }
+
+    /// Exports a session in the given format. See
+    /// [`crate::JulesClient::export_session`].
+    pub fn export_session(&self, session_name: &str, format: ExportFormat) -> Result<String> {
+        self.runtime
+            .block_on(self.inner.export_session(session_name, format))
+    }
+
+    /// Returns the most recent patch produced by a session.
+    pub fn latest_patch(&self, session_name: &str) -> Result<Option<GitPatch>> {
+        self.runtime.block_on(self.inner.latest_patch(session_name))
+    }
+
+    // --- Sources API ---
+
+    /// Gets a source by its resource name.
+    pub fn get_source(&self, name: &str) -> Result<Source> {
+        self.runtime.block_on(self.inner.get_source(name))
+    }
+
+    /// Lists one page of sources. See [`crate::JulesClient::list_sources`].
+    pub fn list_sources(
+        &self,
+        filter: Option<String>,
+        page_size: Option<i32>,
+        page_token: Option<String>,
+    ) -> Result<ListSourcesResponse> {
+        self.runtime
+            .block_on(self.inner.list_sources(filter, page_size, page_token))
+    }
+
+    /// Returns an iterator over all sources. See
+    /// [`crate::JulesClient::stream_sources`].
+    pub fn iter_sources(&self) -> Iter<'_, Source> {
+        self.iter(self.inner.stream_sources())
+    }
+
+    /// Finds the source for a GitHub repository. See
+    /// [`crate::JulesClient::find_github_source`].
+    pub fn find_github_source(&self, owner: &str, repo: &str) -> Result<Option<Source>> {
+        self.runtime
+            .block_on(self.inner.find_github_source(owner, repo))
+    }
+
+    /// Finds the source a pull request belongs to. See
+    /// [`crate::JulesClient::pull_request_source`].
+    pub fn pull_request_source(&self, pr: &PullRequest) -> Result<Option<Source>> {
+        self.runtime.block_on(self.inner.pull_request_source(pr))
+    }
+}
+
+/// A blocking iterator over a paginated listing or a watched session.
+///
+/// Each call to `next` may make API requests. After an error, the iterator
+/// ends.
+pub struct Iter<'a, T> {
+    runtime: &'a Runtime,
+    stream: Pin<Box<dyn Stream<Item = Result<T>> + 'a>>,
+}
+
+impl<T> Iterator for Iter<'_, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.runtime.block_on(self.stream.next())
+    }
+}
+
+impl<T> std::fmt::Debug for Iter<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Iter").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::{HttpTransport, Request, Response};
+    use async_trait::async_trait;
+    use serde_json::json;
+
+    /// Serves sessions `1` and `2` on two pages.
+    struct Fake;
+
+    #[async_trait]
+    impl HttpTransport for Fake {
+        async fn send(&self, request: Request) -> Result<Response> {
+            let second_page = request
+                .uri()
+                .query()
+                .is_some_and(|q| q.contains("pageToken=2"));
+            let session = |id: u32| {
+                json!({
+                    "name": format!("sessions/{id}"),
+                    "prompt": "p",
+                    "sourceContext": { "source": "sources/github/acme/widgets" },
+                    "state": "COMPLETED",
+                })
+            };
+            let body = match request.uri().path() {
+                "/v1alpha/sessions/1" => session(1),
+                _ if second_page => json!({ "sessions": [session(2)] }),
+                _ => json!({ "sessions": [session(1)], "nextPageToken": "2" }),
+            };
+            Ok(Response::new(serde_json::to_vec(&body)?))
+        }
+    }
+
+    #[test]
+    fn calls_and_iterates_over_pages() {
+        let client =
+            JulesClient::from_async(crate::JulesClient::new("key").unwrap().with_transport(Fake))
+                .unwrap();
+
+        let session = client.get_session("sessions/1").unwrap();
+        assert_eq!(session.state, Some(SessionState::Completed));
+
+        let names: Vec<_> = client
+            .iter_sessions()
+            .map(|s| s.unwrap().name.unwrap())
+            .collect();
+        assert_eq!(names, ["sessions/1", "sessions/2"]);
+    }
+}
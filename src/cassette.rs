@@ -0,0 +1,552 @@
+//! Recording API traffic and replaying it without a network.
+//!
+//! A [`Recorder`] is [middleware](crate::middleware) that, attached to a
+//! client with [`JulesClient::with_recorder`], captures every request and
+//! response. The
+//! result is a [`Cassette`], saved as a JSON file. A client created with
+//! [`JulesClient::replaying`] answers requests from a cassette instead of
+//! the network, so tests built from real recorded sessions run
+//! deterministically in CI.
+//!
+//! Cassettes never contain credentials: request headers, including the API
+//! key, are not recorded, the API key is replaced with `REDACTED` wherever
+//! it appears in URLs or bodies, and page tokens are replaced with stable
+//! placeholders. Further secrets can be scrubbed with [`Recorder::scrub`].
+//!
+//! This module is only available with the `mock` feature.
+//!
+//! # Example
+//!
+//! Record once against the real API:
+//!
+//! ```rust,no_run
+//! use jules_rs::JulesClient;
+//! use jules_rs::cassette::Recorder;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let recorder = Recorder::new();
+//! let client = JulesClient::new(std::env::var("JULES_API_KEY")?)?.with_recorder(&recorder);
+//! client.list_sessions(Some(5), None).await?;
+//! recorder.save("tests/cassettes/list_sessions.json")?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Then replay it in tests:
+//!
+//! ```rust,no_run
+//! use jules_rs::JulesClient;
+//! use jules_rs::cassette::Cassette;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = JulesClient::replaying(Cassette::load("tests/cassettes/list_sessions.json")?);
+//! let sessions = client.list_sessions(Some(5), None).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::JulesClient;
+use crate::error::{JulesError, Result};
+use crate::middleware::{Middleware, Next};
+use crate::transport::{HttpTransport, Request, Response};
+use async_trait::async_trait;
+use http::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// The cassette file format version written by this crate.
+pub const CASSETTE_VERSION: u32 = 1;
+
+/// The text that replaces the API key and other scrubbed secrets.
+pub const REDACTED: &str = "REDACTED";
+
+/// A recorded sequence of requests and responses.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cassette {
+    /// The file format version.
+    pub version: u32,
+    /// The recorded interactions, in the order they happened.
+    pub interactions: Vec<Interaction>,
+}
+
+impl Default for Cassette {
+    fn default() -> Self {
+        Self {
+            version: CASSETTE_VERSION,
+            interactions: Vec::new(),
+        }
+    }
+}
+
+impl Cassette {
+    /// Reads a cassette from a JSON file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    /// Writes the cassette to a JSON file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)? + "\n")?;
+        Ok(())
+    }
+}
+
+/// A request and the response it received.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    /// The request.
+    pub request: RecordedRequest,
+    /// The response.
+    pub response: RecordedResponse,
+}
+
+/// A recorded request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    /// The HTTP method, e.g. `GET`.
+    pub method: String,
+    /// The path relative to the API base URL, e.g. `sessions/123`.
+    pub path: String,
+    /// The query parameters.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub query: Vec<(String, String)>,
+    /// The JSON request body, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+}
+
+/// A recorded response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    /// The HTTP status code.
+    pub status: u16,
+    /// The body, if it was JSON.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+    /// The body, if it was not JSON.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct Recording {
+    cassette: Cassette,
+    secrets: Vec<String>,
+    page_tokens: HashMap<String, String>,
+}
+
+impl Recording {
+    fn scrub(&self, text: &str, api_key: &str) -> String {
+        let mut text = text.to_string();
+        for secret in self.secrets.iter().map(String::as_str).chain([api_key]) {
+            if !secret.is_empty() {
+                text = text.replace(secret, REDACTED);
+            }
+        }
+        text
+    }
+
+    /// Returns the placeholder for a page token, assigning one if new.
+    fn page_token(&mut self, token: &str) -> String {
+        let next = self.page_tokens.len() + 1;
+        self.page_tokens
+            .entry(token.to_string())
+            .or_insert_with(|| format!("page-token-{next}"))
+            .clone()
+    }
+}
+
+/// Captures a client's requests and responses into a [`Cassette`].
+///
+/// Clones share the same recording.
+#[derive(Debug, Clone, Default)]
+pub struct Recorder {
+    inner: Arc<Mutex<Recording>>,
+}
+
+impl Recorder {
+    /// Creates an empty recorder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces `secret` with `REDACTED` in everything recorded from now on.
+    pub fn scrub(&self, secret: impl Into<String>) {
+        self.lock().secrets.push(secret.into());
+    }
+
+    /// Returns the interactions recorded so far.
+    pub fn cassette(&self) -> Cassette {
+        self.lock().cassette.clone()
+    }
+
+    /// Writes the interactions recorded so far to a JSON file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        self.cassette().save(path)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Recording> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn record(&self, request: &RequestCopy, base: &url::Url, response: &Response) {
+        let api_key = request.api_key.as_str();
+        let mut recording = self.lock();
+        let (path, mut query) = split_url(&request.url, base, &recording, api_key);
+        for (name, value) in &mut query {
+            if name == "pageToken" && !value.is_empty() {
+                *value = recording.page_token(value);
+            }
+        }
+        let request_body = serde_json::from_str(
+            &recording.scrub(&String::from_utf8_lossy(&request.body), api_key),
+        )
+        .ok();
+
+        let text = recording.scrub(&String::from_utf8_lossy(response.body()), api_key);
+        let status = response.status();
+        let mut response = RecordedResponse {
+            status: status.as_u16(),
+            body: None,
+            text: None,
+        };
+        match serde_json::from_str::<Value>(&text) {
+            Ok(mut value) => {
+                if let Some(Value::String(token)) = value.get("nextPageToken") {
+                    let placeholder = recording.page_token(&token.clone());
+                    value["nextPageToken"] = Value::String(placeholder);
+                }
+                response.body = Some(value);
+            }
+            Err(_) => response.text = Some(text),
+        }
+
+        recording.cassette.interactions.push(Interaction {
+            request: RecordedRequest {
+                method: request.method.clone(),
+                path,
+                query,
+                body: request_body,
+            },
+            response,
+        });
+    }
+}
+
+/// The parts of a request the recorder keeps while it is sent.
+struct RequestCopy {
+    method: String,
+    url: url::Url,
+    body: Vec<u8>,
+    api_key: String,
+}
+
+impl RequestCopy {
+    fn new(request: &Request) -> Option<Self> {
+        Some(Self {
+            method: request.method().to_string(),
+            url: url::Url::parse(&request.uri().to_string()).ok()?,
+            body: request.body().clone(),
+            api_key: request
+                .headers()
+                .get("X-Goog-Api-Key")
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or_default()
+                .to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl Middleware for Recorder {
+    async fn handle(&self, request: Request, next: Next<'_>) -> Result<Response> {
+        let copy = RequestCopy::new(&request);
+        let base = next.client().base_url.clone();
+        let response = next.run(request).await?;
+        if let Some(request) = copy {
+            self.record(&request, &base, &response);
+        }
+        Ok(response)
+    }
+}
+
+/// Answers requests from a cassette.
+#[derive(Debug)]
+struct Player {
+    base: url::Url,
+    inner: Mutex<Playback>,
+}
+
+#[derive(Debug)]
+struct Playback {
+    cassette: Cassette,
+    used: Vec<bool>,
+}
+
+#[async_trait]
+impl HttpTransport for Player {
+    async fn send(&self, request: Request) -> Result<Response> {
+        let url = url::Url::parse(&request.uri().to_string())?;
+        let (path, query) = split_url(&url, &self.base, &Recording::default(), "");
+        let method = request.method().to_string();
+        let mut playback = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let (status, body) = playback.play(&method, &path, &query)?;
+        let mut response = Response::new(body);
+        *response.status_mut() = status;
+        Ok(response)
+    }
+}
+
+impl Playback {
+    /// Finds the first unused interaction matching the request. Once all
+    /// matching interactions are used, the last one is served again, so
+    /// polling loops keep seeing the final recorded state.
+    fn play(
+        &mut self,
+        method: &str,
+        path: &str,
+        query: &[(String, String)],
+    ) -> Result<(StatusCode, Vec<u8>)> {
+        let matching: Vec<usize> = self
+            .cassette
+            .interactions
+            .iter()
+            .enumerate()
+            .filter(|(_, i)| {
+                i.request.method == method && i.request.path == path && i.request.query == query
+            })
+            .map(|(index, _)| index)
+            .collect();
+        let index = matching
+            .iter()
+            .copied()
+            .find(|&i| !self.used[i])
+            .or_else(|| matching.last().copied())
+            .ok_or_else(|| {
+                let query = query
+                    .iter()
+                    .map(|(k, v)| format!("{k}={v}"))
+                    .collect::<Vec<_>>()
+                    .join("&");
+                let separator = if query.is_empty() { "" } else { "?" };
+                JulesError::Cassette(format!(
+                    "no recorded interaction for {method} {path}{separator}{query}"
+                ))
+            })?;
+        self.used[index] = true;
+
+        let response = &self.cassette.interactions[index].response;
+        let status = StatusCode::from_u16(response.status)
+            .map_err(|e| JulesError::Cassette(e.to_string()))?;
+        let body = match (&response.body, &response.text) {
+            (Some(body), _) => serde_json::to_vec(body)?,
+            (None, Some(text)) => text.clone().into_bytes(),
+            (None, None) => Vec::new(),
+        };
+        Ok((status, body))
+    }
+}
+
+/// Splits a request URL into a path relative to the API base URL and its
+/// query parameters, scrubbing both.
+fn split_url(
+    url: &url::Url,
+    base: &url::Url,
+    recording: &Recording,
+    api_key: &str,
+) -> (String, Vec<(String, String)>) {
+    let path = url.path().strip_prefix(base.path()).unwrap_or(url.path());
+    let query = url
+        .query_pairs()
+        .map(|(k, v)| (k.into_owned(), recording.scrub(&v, api_key)))
+        .collect();
+    (recording.scrub(path, api_key), query)
+}
+
+impl JulesClient {
+    /// Records every request and response of this client into `recorder`.
+    ///
+    /// Requests are still sent to the API. The recorder is added as
+    /// [middleware](crate::middleware), so it records requests as they leave
+    /// the middleware added before it; add it last to record what is sent to
+    /// the API.
+    pub fn with_recorder(self, recorder: &Recorder) -> Self {
+        self.with_middleware(recorder.clone())
+    }
+
+    /// Creates a client that answers every request from a cassette, without
+    /// using the network.
+    ///
+    /// Requests are matched by method, path, and query. Requests that match
+    /// several interactions get them in recorded order; after the last one,
+    /// it is repeated. A request with no recorded interaction fails with
+    /// [`JulesError::Cassette`]. Middleware added to the client runs before
+    /// the cassette is consulted. GitHub lookups are not replayed; they use
+    /// the default transport unless
+    /// [`with_transport`](JulesClient::with_transport) is called.
+    pub fn replaying(cassette: Cassette) -> Self {
+        let mut client = Self::new(REDACTED).expect("the default base URL is valid");
+        let used = vec![false; cassette.interactions.len()];
+        client.transport = Arc::new(Player {
+            base: client.base_url.clone(),
+            inner: Mutex::new(Playback { cassette, used }),
+        });
+        client
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{GitHubRepoContext, Session, SessionState, SourceContext};
+    use crate::testing::{MockServer, Script};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const API_KEY: &str = "api-key-that-must-not-leak";
+    const SECRET: &str = "hunter2";
+
+    fn request(prompt: String) -> Session {
+        Session {
+            name: None,
+            id: None,
+            prompt,
+            source_context: SourceContext {
+                source: "sources/github/acme/widgets".to_string(),
+                github_repo_context: Some(GitHubRepoContext {
+                    starting_branch: "main".to_string(),
+                }),
+            },
+            title: None,
+            require_plan_approval: None,
+            automation_mode: None,
+            create_time: None,
+            update_time: None,
+            state: None,
+            url: None,
+            outputs: None,
+        }
+    }
+
+    /// A recording saved to and loaded from a file.
+    struct Recorded {
+        cassette: Cassette,
+        /// The saved file.
+        text: String,
+        /// The session polled before and after it completed.
+        session: String,
+    }
+
+    /// Records sessions being created, listed over two pages, and polled
+    /// before and after they complete.
+    async fn record() -> Recorded {
+        static FILES: AtomicUsize = AtomicUsize::new(0);
+
+        let server = MockServer::start();
+        server.set_api_key(API_KEY);
+        server.add_github_source("acme", "widgets", &["main"]);
+        let recorder = Recorder::new();
+        recorder.scrub(SECRET);
+        let client = server.client().with_recorder(&recorder);
+
+        let prompt = format!("Use {SECRET} and {API_KEY}");
+        let first = client
+            .create_session(&request(prompt.clone()))
+            .await
+            .unwrap();
+        for _ in 0..2 {
+            client
+                .create_session(&request(prompt.clone()))
+                .await
+                .unwrap();
+        }
+        let page = client.list_sessions(Some(2), None).await.unwrap();
+        let token = page.next_page_token.expect("a second page");
+        client.list_sessions(Some(2), Some(token)).await.unwrap();
+
+        let name = first.name.unwrap();
+        client.get_session(&name).await.unwrap();
+        server.apply(&name, &Script::new().complete());
+        client.get_session(&name).await.unwrap();
+
+        let file = format!(
+            "jules-cassette-{}-{}.json",
+            std::process::id(),
+            FILES.fetch_add(1, Ordering::SeqCst)
+        );
+        let path = std::env::temp_dir().join(file);
+        recorder.save(&path).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        let cassette = Cassette::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(cassette, recorder.cassette());
+        Recorded {
+            cassette,
+            text,
+            session: name,
+        }
+    }
+
+    #[tokio::test]
+    async fn cassettes_contain_no_credentials() {
+        let text = record().await.text;
+        assert!(!text.contains(API_KEY), "{text}");
+        assert!(!text.contains(SECRET), "{text}");
+        assert!(text.contains(&format!("Use {REDACTED} and {REDACTED}")));
+    }
+
+    #[tokio::test]
+    async fn page_tokens_are_replaced_consistently() {
+        let cassette = record().await.cassette;
+        let lists: Vec<_> = cassette
+            .interactions
+            .iter()
+            .filter(|i| i.request.method == "GET" && i.request.path == "sessions")
+            .collect();
+        assert_eq!(lists.len(), 2);
+        let next = lists[0].response.body.as_ref().unwrap()["nextPageToken"].clone();
+        assert_eq!(next, "page-token-1");
+        let query = &lists[1].request.query;
+        assert!(query.contains(&("pageToken".to_string(), "page-token-1".to_string())));
+
+        let client = JulesClient::replaying(cassette);
+        let page = client.list_sessions(Some(2), None).await.unwrap();
+        assert_eq!(page.sessions.len(), 2);
+        let token = page.next_page_token.unwrap();
+        let page = client.list_sessions(Some(2), Some(token)).await.unwrap();
+        assert_eq!(page.sessions.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn replays_in_order_then_repeats_the_last_match() {
+        let recorded = record().await;
+        let client = JulesClient::replaying(recorded.cassette);
+        let mut seen = Vec::new();
+        for _ in 0..3 {
+            seen.push(client.get_session(&recorded.session).await.unwrap().state);
+        }
+        assert_ne!(seen[0], Some(SessionState::Completed));
+        assert_eq!(seen[1], Some(SessionState::Completed));
+        assert_eq!(seen[2], Some(SessionState::Completed));
+    }
+
+    #[tokio::test]
+    async fn unmatched_requests_fail() {
+        let client = JulesClient::replaying(record().await.cassette);
+        match client.get_session("sessions/unrecorded").await {
+            Err(JulesError::Cassette(message)) => {
+                assert!(message.contains("GET sessions/unrecorded"), "{message}");
+            }
+            other => panic!("expected a cassette error, got {other:?}"),
+        }
+        let page = client.list_sessions(Some(3), None).await;
+        assert!(matches!(page, Err(JulesError::Cassette(_))));
+    }
+}
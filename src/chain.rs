@@ -0,0 +1,318 @@
+//! Follow-up sessions that build on a previous session's pull request.
+//!
+//! When a session has opened a pull request, [`JulesClient::continue_from`]
+//! starts a new session on the same source whose starting branch is the pull
+//! request's branch, so the follow-up task sees the earlier changes. The
+//! returned [`ChainedSession`] records which session it continues.
+//!
+//! The Jules API reports a pull request only by its URL, so the branch is
+//! looked up with the GitHub REST API, using the client's transport without
+//! its middleware or layers. Private repositories need a GitHub
+//! token set with [`FollowUp::github_token`]; alternatively, the branch can
+//! be given directly with [`FollowUp::branch`]. Pull requests from forks are
+//! rejected, since their branch does not exist in the session's repository.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use jules_rs::JulesClient;
+//! use jules_rs::chain::FollowUp;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = JulesClient::new("TOKEN")?;
+//! let parent = client.get_session("sessions/abc123").await?;
+//!
+//! let chained = client
+//!     .continue_from(&parent, "Now add tests for the new parser")
+//!     .await?;
+//! println!("continues {} on {}", chained.parent, chained.branch);
+//!
+//! // With a token for a private repository:
+//! let follow_up = FollowUp::new("Update the changelog")
+//!     .github_token(std::env::var("GITHUB_TOKEN")?);
+//! let next = client.continue_from_with(&chained.session, follow_up).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::JulesClient;
+use crate::error::{JulesError, Result};
+use crate::models::{GitHubRepoContext, PullRequest, Session, SourceContext};
+use crate::secret::Secret;
+use crate::transport::RequestBuilder;
+use http::Method;
+use serde::Deserialize;
+use url::Url;
+
+const GITHUB_API: &str = "https://api.github.com";
+
+/// A follow-up task for [`JulesClient::continue_from_with`].
+#[derive(Debug, Clone)]
+pub struct FollowUp {
+    prompt: String,
+    title: Option<String>,
+    branch: Option<String>,
+    github_token: Option<Secret>,
+}
+
+impl FollowUp {
+    /// Creates a follow-up with the given prompt.
+    pub fn new(prompt: impl Into<String>) -> Self {
+        Self {
+            prompt: prompt.into(),
+            title: None,
+            branch: None,
+            github_token: None,
+        }
+    }
+
+    /// Sets the title of the new session.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Uses this branch instead of looking up the pull request's branch.
+    pub fn branch(mut self, branch: impl Into<String>) -> Self {
+        self.branch = Some(branch.into());
+        self
+    }
+
+    /// Sets the GitHub token used to look up the pull request's branch.
+    pub fn github_token(mut self, token: impl Into<Secret>) -> Self {
+        self.github_token = Some(token.into());
+        self
+    }
+}
+
+/// A session started from another session's pull request.
+#[derive(Debug, Clone)]
+pub struct ChainedSession {
+    /// The new session.
+    pub session: Session,
+    /// Resource name of the session it continues.
+    pub parent: String,
+    /// The parent's pull request the new session starts from.
+    pub pull_request: PullRequest,
+    /// The branch the new session starts from.
+    pub branch: String,
+}
+
+impl JulesClient {
+    /// Starts a follow-up session on the branch of a session's pull request.
+    ///
+    /// The new session uses the parent's source and plan approval and
+    /// automation settings. If `parent` has no outputs, for example because
+    /// it was returned by [`create_session`](Self::create_session), it is
+    /// fetched again first. See [`continue_from_with`](Self::continue_from_with)
+    /// for more options.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JulesError::NoPullRequest`] if the session has not created
+    /// a pull request, [`JulesError::GitHub`] if the branch cannot be looked
+    /// up on GitHub, [`JulesError::ForkPullRequest`] if the pull request's
+    /// branch lives in a fork, or an API error if the session cannot be
+    /// created.
+    pub async fn continue_from(&self, parent: &Session, prompt: &str) -> Result<ChainedSession> {
+        self.continue_from_with(parent, FollowUp::new(prompt)).await
+    }
+
+    /// Starts a follow-up session on the branch of a session's pull request,
+    /// with a title, a known branch, or a GitHub token.
+    ///
+    /// If the session has opened more than one pull request, the latest is
+    /// used.
+    ///
+    /// # Errors
+    ///
+    /// See [`continue_from`](Self::continue_from).
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(parent = ?parent.name))
+    )]
+    pub async fn continue_from_with(
+        &self,
+        parent: &Session,
+        follow_up: FollowUp,
+    ) -> Result<ChainedSession> {
+        let parent_name = parent.name.clone().ok_or_else(|| {
+            JulesError::InvalidResourceName("the parent session has no name".to_string())
+        })?;
+        let fetched;
+        let parent = if parent.outputs.is_none() {
+            fetched = self.get_session(&parent_name).await?;
+            &fetched
+        } else {
+            parent
+        };
+        let pull_request = parent
+            .pull_requests()
+            .next_back()
+            .cloned()
+            .ok_or_else(|| JulesError::NoPullRequest(parent_name.clone()))?;
+
+        let branch = match follow_up.branch {
+            Some(branch) => branch,
+            None => {
+                self.pull_request_branch(&pull_request, follow_up.github_token.as_ref())
+                    .await?
+            }
+        };
+
+        let request = Session {
+            name: None,
+            id: None,
+            prompt: follow_up.prompt,
+            source_context: SourceContext {
+                source: parent.source_context.source.clone(),
+                github_repo_context: Some(GitHubRepoContext {
+                    starting_branch: branch.clone(),
+                }),
+            },
+            title: follow_up.title,
+            require_plan_approval: parent.require_plan_approval,
+            automation_mode: parent.automation_mode.clone(),
+            create_time: None,
+            update_time: None,
+            state: None,
+            url: None,
+            outputs: None,
+        };
+        let session = self.create_session(&request).await?;
+        Ok(ChainedSession {
+            session,
+            parent: parent_name,
+            pull_request,
+            branch,
+        })
+    }
+
+    /// Looks up the head branch of a pull request with the GitHub REST API.
+    async fn pull_request_branch(
+        &self,
+        pr: &PullRequest,
+        token: Option<&Secret>,
+    ) -> Result<String> {
+        #[derive(Deserialize)]
+        struct GitHubPull {
+            head: GitHubRef,
+        }
+        #[derive(Deserialize)]
+        struct GitHubRef {
+            #[serde(rename = "ref")]
+            name: String,
+            repo: Option<GitHubRepository>,
+        }
+        #[derive(Deserialize)]
+        struct GitHubRepository {
+            full_name: String,
+        }
+
+        let (Some(owner), Some(repo), Some(number)) = (pr.owner(), pr.repo(), pr.number()) else {
+            return Err(JulesError::InvalidResourceName(format!(
+                "not a GitHub pull request URL: {}",
+                pr.url
+            )));
+        };
+        let url = Url::parse(&format!("{GITHUB_API}/repos/{owner}/{repo}/pulls/{number}"))?;
+        let mut rb = RequestBuilder::new(Method::GET, url)
+            .header("accept", "application/vnd.github+json")
+            .header(
+                "user-agent",
+                concat!("jules-rs/", env!("CARGO_PKG_VERSION")),
+            );
+        if let Some(token) = token {
+            rb = rb.sensitive_header("authorization", &format!("Bearer {}", token.expose()));
+        }
+        let response = self.github.send(rb.build()?).await?;
+        if !response.status().is_success() {
+            return Err(JulesError::GitHub {
+                status: response.status(),
+                message: String::from_utf8_lossy(response.body()).into_owned(),
+            });
+        }
+        let pull: GitHubPull = serde_json::from_slice(response.body())?;
+        // The head repository is null when a fork has been deleted.
+        let head_repo = pull
+            .head
+            .repo
+            .map(|r| r.full_name)
+            .unwrap_or_else(|| "(deleted fork)".to_string());
+        if !head_repo.eq_ignore_ascii_case(&format!("{owner}/{repo}")) {
+            return Err(JulesError::ForkPullRequest {
+                url: pr.url.clone(),
+                head_repo,
+            });
+        }
+        Ok(pull.head.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::{HttpTransport, Request, Response};
+    use async_trait::async_trait;
+    use serde_json::json;
+
+    /// Answers the GitHub pull request lookup with `head`, and echoes
+    /// created sessions with a name.
+    struct Fake {
+        head: serde_json::Value,
+    }
+
+    #[async_trait]
+    impl HttpTransport for Fake {
+        async fn send(&self, request: Request) -> Result<Response> {
+            let body = if request.uri().host() == Some("api.github.com") {
+                assert_eq!(request.uri().path(), "/repos/acme/widgets/pulls/7");
+                json!({ "head": self.head })
+            } else {
+                let mut session: serde_json::Value = serde_json::from_slice(request.body())?;
+                session["name"] = json!("sessions/2");
+                session
+            };
+            Ok(Response::new(serde_json::to_vec(&body)?))
+        }
+    }
+
+    fn parent() -> Session {
+        serde_json::from_value(json!({
+            "name": "sessions/1",
+            "prompt": "Fix the bug",
+            "sourceContext": { "source": "sources/github/acme/widgets" },
+            "outputs": [{ "pullRequest": {
+                "url": "https://github.com/acme/widgets/pull/7",
+                "title": "Fix the bug",
+                "description": "",
+            } }],
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn starts_from_the_pull_request_branch() {
+        let client = JulesClient::new("key").unwrap().with_transport(Fake {
+            head: json!({ "ref": "jules-fix", "repo": { "full_name": "Acme/Widgets" } }),
+        });
+        let chained = client.continue_from(&parent(), "Add tests").await.unwrap();
+        assert_eq!(chained.branch, "jules-fix");
+        assert_eq!(chained.parent, "sessions/1");
+        let context = chained.session.source_context.github_repo_context.unwrap();
+        assert_eq!(context.starting_branch, "jules-fix");
+    }
+
+    #[tokio::test]
+    async fn rejects_pull_requests_from_forks() {
+        let client = JulesClient::new("key").unwrap().with_transport(Fake {
+            head: json!({ "ref": "main", "repo": { "full_name": "someone/widgets" } }),
+        });
+        match client.continue_from(&parent(), "Add tests").await {
+            Err(JulesError::ForkPullRequest { head_repo, .. }) => {
+                assert_eq!(head_repo, "someone/widgets");
+            }
+            other => panic!("expected a fork error, got {other:?}"),
+        }
+    }
+}
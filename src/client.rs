@@ -3,12 +3,24 @@
 //! This module provides the main [`JulesClient`] struct for interacting with
 //! the Jules API endpoints.
 
+use crate::commands::CommandLog;
 use crate::error::{JulesError, Result};
+use crate::export::{ExportFormat, Transcript};
+use crate::metrics::{Metrics, RequestMetrics};
+use crate::middleware::{Middleware, Next};
 use crate::models::*;
+use crate::secret::Secret;
+use crate::transport::{HttpTransport, Request, RequestBuilder, Response, Sleep};
 use futures_util::{StreamExt, stream::Stream};
-use reqwest::{Client, Method, RequestBuilder};
+use http::{Method, StatusCode};
 use serde::Deserialize;
+use std::collections::HashSet;
+use std::future::Future;
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Instant;
 use url::Url;
 
 /// The main client for interacting with the Jules API.
@@ -31,9 +43,143 @@ use url::Url;
 /// # }
 /// ```
 pub struct JulesClient {
-    http: Client,
-    base_url: Url,
-    token: String,
+    pub(crate) base_url: Url,
+    token: Secret,
+    pub(crate) middleware: Vec<Arc<dyn Middleware>>,
+    /// Sends Jules API requests, possibly through a `with_layer` stack or a
+    /// cassette.
+    pub(crate) transport: Arc<dyn HttpTransport>,
+    /// Sends GitHub API requests; the transport without layers.
+    pub(crate) github: Arc<dyn HttpTransport>,
+    pub(crate) sleep: Option<Arc<dyn Sleep>>,
+    pub(crate) metrics: Option<Arc<dyn Metrics>>,
+}
+
+impl std::fmt::Debug for JulesClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut base_url = self.base_url.clone();
+        redact_url(&mut base_url, self.token.expose());
+        f.debug_struct("JulesClient")
+            .field("base_url", &base_url.as_str())
+            .field("token", &self.token)
+            .field("middleware", &self.middleware.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// An API call made by a client method, for instrumentation.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Call {
+    /// The client method, e.g. `get_session`.
+    pub(crate) method: &'static str,
+    /// The page number within a `stream_*` method, starting at 1.
+    #[cfg_attr(not(feature = "tracing"), allow(dead_code))]
+    pub(crate) page: Option<usize>,
+}
+
+impl Call {
+    pub(crate) const fn new(method: &'static str) -> Self {
+        Self { method, page: None }
+    }
+
+    pub(crate) const fn page(mut self, page: Option<usize>) -> Self {
+        self.page = page;
+        self
+    }
+}
+
+/// The resource a request path refers to, without the base path or custom
+/// method, e.g. `sessions/123` for `/v1alpha/sessions/123:approvePlan`.
+#[cfg(feature = "tracing")]
+fn resource_name(base: &Url, path: &str) -> String {
+    let path = path.strip_prefix(base.path()).unwrap_or(path);
+    path.split(':').next().unwrap_or(path).to_string()
+}
+
+/// Makes a server-supplied ID safe to use as a file name.
+fn file_stem(id: &str) -> String {
+    id.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Streams the items of every page returned by `fetch`, which is called with
+/// the page token (`None` for the first page) and the 1-based page number and
+/// returns the page's items and next page token.
+///
+/// Ends with [`JulesError::PageTokenLoop`] if the API returns a page token it
+/// has returned before, and with the error of a failed page.
+fn paginate<'a, T, Fut>(
+    mut fetch: impl FnMut(Option<String>, usize) -> Fut + 'a,
+) -> Pin<Box<dyn Stream<Item = Result<T>> + 'a>>
+where
+    T: 'a,
+    Fut: Future<Output = Result<(Vec<T>, Option<String>)>> + 'a,
+{
+    let start = Some((None::<String>, 1, HashSet::new()));
+    Box::pin(
+        futures_util::stream::unfold(start, move |state| {
+            let request = state.map(|(token, page, seen)| (fetch(token, page), page, seen));
+            async move {
+                let (request, page, mut seen) = request?;
+                let (items, next_token) = match request.await {
+                    Ok(page) => page,
+                    Err(e) => return Some((futures_util::stream::iter(vec![Err(e)]), None)),
+                };
+                let mut items: Vec<Result<T>> = items.into_iter().map(Ok).collect();
+                let next_state = match next_token.filter(|t| !t.is_empty()) {
+                    None => None,
+                    Some(token) if !seen.insert(token.clone()) => {
+                        items.push(Err(JulesError::PageTokenLoop(token)));
+                        None
+                    }
+                    Some(token) => Some((Some(token), page + 1, seen)),
+                };
+                Some((futures_util::stream::iter(items), next_state))
+            }
+        })
+        .flatten(),
+    )
+}
+
+/// Query parameters that carry credentials.
+const SECRET_PARAMS: &[&str] = &["key", "api_key", "apiKey", "access_token", "token"];
+
+/// Replaces credentials in a URL: user info, credential query parameters,
+/// and any occurrence of the API key.
+fn redact_url(url: &mut Url, api_key: &str) {
+    const REDACTED: &str = "REDACTED";
+    if !url.username().is_empty() {
+        let _ = url.set_username(REDACTED);
+    }
+    if url.password().is_some() {
+        let _ = url.set_password(Some(REDACTED));
+    }
+    if url.query().is_some() {
+        let pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .map(|(k, v)| {
+                let v = if SECRET_PARAMS.contains(&k.as_ref()) {
+                    REDACTED.into()
+                } else {
+                    v
+                };
+                (k.into_owned(), v.into_owned())
+            })
+            .collect();
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    if !api_key.is_empty() && url.as_str().contains(api_key) {
+        if let Ok(redacted) = Url::parse(&url.as_str().replace(api_key, REDACTED)) {
+            *url = redacted;
+        }
+    }
 }
 
 impl JulesClient {
@@ -55,33 +201,147 @@ impl JulesClient {
     ///
     /// let client = JulesClient::new("YOUR_API_KEY").unwrap();
     /// ```
-    pub fn new(token: impl Into<String>) -> Result<Self> {
+    pub fn new(token: impl Into<Secret>) -> Result<Self> {
+        Self::with_base_url(token, "https://jules.googleapis.com/v1alpha/")
+    }
+
+    /// Creates a client for a different API endpoint, such as a proxy or a
+    /// mock server from the `testing` module.
+    ///
+    /// `base_url` is the URL that resource names are resolved against, e.g.
+    /// `https://jules.googleapis.com/v1alpha/`. A trailing `/` is added if
+    /// missing.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JulesError::Url`] if `base_url` is not a valid URL.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use jules_rs::JulesClient;
+    ///
+    /// let client = JulesClient::with_base_url("YOUR_API_KEY", "http://localhost:8080/v1alpha").unwrap();
+    /// ```
+    pub fn with_base_url(token: impl Into<Secret>, base_url: &str) -> Result<Self> {
+        let mut base_url = Url::parse(base_url)?;
+        if !base_url.path().ends_with('/') {
+            let path = format!("{}/", base_url.path());
+            base_url.set_path(&path);
+        }
+        let transport = crate::transport::default_transport();
         Ok(Self {
-            http: Client::new(),
-            base_url: Url::parse("https://jules.googleapis.com/v1alpha/")?,
+            github: Arc::clone(&transport),
+            transport,
+            base_url,
             token: token.into(),
+            middleware: Vec::new(),
+            sleep: crate::transport::default_sleep(),
+            metrics: None,
         })
     }
 
     fn request(&self, method: Method, path: &str) -> RequestBuilder {
         let url = self.base_url.join(path).expect("Path joining failed");
-        self.http
-            .request(method, url)
-            .header("X-Goog-Api-Key", &self.token)
-            .header("Accept", "application/json")
+        RequestBuilder::new(method, url)
+            .header("accept", "application/json")
+            .sensitive_header("x-goog-api-key", self.token.expose())
     }
 
-    async fn execute<T>(&self, builder: RequestBuilder) -> Result<T>
+    async fn execute<T>(&self, call: Call, builder: RequestBuilder) -> Result<T>
     where
         T: for<'de> Deserialize<'de>,
     {
-        let response = builder.send().await?;
-        if !response.status().is_success() {
-            let status = response.status();
-            let message = response.text().await.unwrap_or_default();
+        let (status, body) = self.send(call, builder).await?;
+        if !status.is_success() {
+            let message = String::from_utf8_lossy(&body).into_owned();
             return Err(JulesError::Api { status, message });
         }
-        Ok(response.json().await?)
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    /// Sends a request and returns the response status and body.
+    ///
+    /// The call is reported to the client's [`Metrics`], and with the `tracing`
+    /// feature it is wrapped in a `jules_api` span.
+    pub(crate) async fn send(
+        &self,
+        call: Call,
+        builder: RequestBuilder,
+    ) -> Result<(StatusCode, Vec<u8>)> {
+        let request = builder.build()?;
+        let attempts = AtomicU32::new(0);
+        let started = Instant::now();
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!(
+                "jules_api",
+                method = call.method,
+                resource = %resource_name(&self.base_url, request.uri().path()),
+                page = call.page,
+                status = tracing::field::Empty,
+                latency_ms = tracing::field::Empty,
+                retries = tracing::field::Empty,
+        );
+
+        let result = self.dispatch(request, &attempts);
+        #[cfg(feature = "tracing")]
+        let result = tracing::Instrument::instrument(result, span.clone());
+        let result = result.await;
+        #[cfg(feature = "reqwest")]
+        let result = result.map_err(|e| self.redact(e));
+
+        let latency = started.elapsed();
+        let retries = attempts.load(Ordering::Relaxed).saturating_sub(1);
+        if let Some(metrics) = &self.metrics {
+            metrics.on_request(&RequestMetrics {
+                method: call.method,
+                status: result.as_ref().ok().map(|(status, _)| *status),
+                latency,
+                retries,
+            });
+        }
+        #[cfg(feature = "tracing")]
+        {
+            span.record("latency_ms", latency.as_millis() as u64);
+            span.record("retries", retries);
+            match &result {
+                Ok((status, _)) => {
+                    span.record("status", status.as_u16());
+                }
+                Err(e) => tracing::debug!(parent: &span, error = %e, "request failed"),
+            }
+        }
+        result
+    }
+
+    async fn dispatch(
+        &self,
+        request: Request,
+        attempts: &AtomicU32,
+    ) -> Result<(StatusCode, Vec<u8>)> {
+        let response = Next::new(self, &self.middleware, attempts)
+            .run(request)
+            .await?;
+        Ok((response.status(), response.into_body()))
+    }
+
+    /// Removes credentials from the URL of a `reqwest` error.
+    #[cfg(feature = "reqwest")]
+    fn redact(&self, mut error: JulesError) -> JulesError {
+        if let JulesError::Http(e) = &mut error {
+            if let Some(url) = e
+                .downcast_mut::<reqwest::Error>()
+                .and_then(reqwest::Error::url_mut)
+            {
+                redact_url(url, self.token.expose());
+            }
+        }
+        error
+    }
+
+    /// Sends a request that has passed through the middleware.
+    pub(crate) async fn transport(&self, request: Request) -> Result<Response> {
+        self.transport.send(request).await
     }
 
     // --- Sessions API ---
@@ -122,7 +382,7 @@ impl JulesClient {
     /// ```
     pub async fn create_session(&self, session: &Session) -> Result<Session> {
         let rb = self.request(Method::POST, "sessions").json(session);
-        self.execute(rb).await
+        self.execute(Call::new("create_session"), rb).await
     }
 
     /// Gets a session by its resource name.
@@ -131,7 +391,8 @@ impl JulesClient {
     ///
     /// * `name` - The full resource name (e.g., `sessions/abc123`).
     pub async fn get_session(&self, name: &str) -> Result<Session> {
-        self.execute(self.request(Method::GET, name)).await
+        let rb = self.request(Method::GET, name);
+        self.execute(Call::new("get_session"), rb).await
     }
 
     /// Deletes a session.
@@ -140,7 +401,8 @@ impl JulesClient {
     ///
     /// * `name` - The full resource name of the session to delete.
     pub async fn delete_session(&self, name: &str) -> Result<()> {
-        let _: Empty = self.execute(self.request(Method::DELETE, name)).await?;
+        let rb = self.request(Method::DELETE, name);
+        let _: Empty = self.execute(Call::new("delete_session"), rb).await?;
         Ok(())
     }
 
@@ -158,21 +420,33 @@ impl JulesClient {
         &self,
         page_size: Option<i32>,
         page_token: Option<String>,
+    ) -> Result<ListSessionsResponse> {
+        self.list_sessions_page(page_size, page_token, None).await
+    }
+
+    async fn list_sessions_page(
+        &self,
+        page_size: Option<i32>,
+        page_token: Option<String>,
+        page: Option<usize>,
     ) -> Result<ListSessionsResponse> {
         let mut rb = self.request(Method::GET, "sessions");
         if let Some(ps) = page_size {
-            rb = rb.query(&[("pageSize", ps)]);
+            rb = rb.query("pageSize", ps);
         }
         if let Some(pt) = page_token {
-            rb = rb.query(&[("pageToken", pt)]);
+            rb = rb.query("pageToken", pt);
         }
-        self.execute(rb).await
+        self.execute(Call::new("list_sessions").page(page), rb)
+            .await
     }
 
     /// Returns an async stream over all sessions.
     ///
     /// This method automatically handles pagination, yielding sessions one at
-    /// a time until all sessions have been retrieved.
+    /// a time until all sessions have been retrieved. If the API returns a
+    /// page token it has returned before, the stream ends with
+    /// [`JulesError::PageTokenLoop`] instead of looping.
     ///
     /// # Example
     ///
@@ -192,35 +466,12 @@ impl JulesClient {
     /// # }
     /// ```
     pub fn stream_sessions(&self) -> Pin<Box<dyn Stream<Item = Result<Session>> + '_>> {
-        Box::pin(
-            futures_util::stream::unfold(Some("".to_string()), move |state| async move {
-                let current_token = state?;
-                let token_opt = if current_token.is_empty() {
-                    None
-                } else {
-                    Some(current_token)
-                };
-
-                match self.list_sessions(Some(100), token_opt).await {
-                    Ok(resp) => {
-                        let next_token = resp.next_page_token.clone().unwrap_or_default();
-                        let next_state = if next_token.is_empty() {
-                            None
-                        } else {
-                            Some(next_token)
-                        };
-                        let items: Vec<Result<Session>> =
-                            resp.sessions.into_iter().map(Ok).collect();
-                        Some((futures_util::stream::iter(items), next_state))
-                    }
-                    Err(e) => {
-                        let items: Vec<Result<Session>> = vec![Err(e)];
-                        Some((futures_util::stream::iter(items), None))
-                    }
-                }
-            })
-            .flatten(),
-        )
+        paginate(move |token, page| async move {
+            let resp = self
+                .list_sessions_page(Some(100), token, Some(page))
+                .await?;
+            Ok((resp.sessions, resp.next_page_token))
+        })
     }
 
     /// Sends a message to an active session.
@@ -237,9 +488,8 @@ impl JulesClient {
         let body = SendMessageRequest {
             prompt: prompt.to_string(),
         };
-        let _: Empty = self
-            .execute(self.request(Method::POST, &path).json(&body))
-            .await?;
+        let rb = self.request(Method::POST, &path).json(&body);
+        let _: Empty = self.execute(Call::new("send_message"), rb).await?;
         Ok(())
     }
 
@@ -254,9 +504,8 @@ impl JulesClient {
     pub async fn approve_plan(&self, session_name: &str) -> Result<()> {
         let path = format!("{}:approvePlan", session_name);
         let body = ApprovePlanRequest {};
-        let _: Empty = self
-            .execute(self.request(Method::POST, &path).json(&body))
-            .await?;
+        let rb = self.request(Method::POST, &path).json(&body);
+        let _: Empty = self.execute(Call::new("approve_plan"), rb).await?;
         Ok(())
     }
 
@@ -268,7 +517,8 @@ impl JulesClient {
     ///
     /// * `name` - The full resource name (e.g., `sessions/123/activities/456`).
     pub async fn get_activity(&self, name: &str) -> Result<Activity> {
-        self.execute(self.request(Method::GET, name)).await
+        let rb = self.request(Method::GET, name);
+        self.execute(Call::new("get_activity"), rb).await
     }
 
     /// Lists activities for a session with pagination.
@@ -283,16 +533,216 @@ impl JulesClient {
         session_name: &str,
         page_size: Option<i32>,
         page_token: Option<String>,
+    ) -> Result<ListActivitiesResponse> {
+        self.list_activities_page(session_name, page_size, page_token, None)
+            .await
+    }
+
+    async fn list_activities_page(
+        &self,
+        session_name: &str,
+        page_size: Option<i32>,
+        page_token: Option<String>,
+        page: Option<usize>,
     ) -> Result<ListActivitiesResponse> {
         let path = format!("{}/activities", session_name);
         let mut rb = self.request(Method::GET, &path);
         if let Some(ps) = page_size {
-            rb = rb.query(&[("pageSize", ps)]);
+            rb = rb.query("pageSize", ps);
         }
         if let Some(pt) = page_token {
-            rb = rb.query(&[("pageToken", pt)]);
+            rb = rb.query("pageToken", pt);
+        }
+        self.execute(Call::new("list_activities").page(page), rb)
+            .await
+    }
+
+    /// Returns an async stream over all activities of a session.
+    ///
+    /// This method automatically handles pagination, yielding activities in
+    /// the order returned by the API until all have been retrieved.
+    ///
+    /// # Arguments
+    ///
+    /// * `session_name` - The full resource name of the session.
+    pub fn stream_activities<'a>(
+        &'a self,
+        session_name: &'a str,
+    ) -> Pin<Box<dyn Stream<Item = Result<Activity>> + 'a>> {
+        paginate(move |token, page| async move {
+            let resp = self
+                .list_activities_page(session_name, Some(100), token, Some(page))
+                .await?;
+            Ok((resp.activities, resp.next_page_token))
+        })
+    }
+
+    /// Downloads every media artifact of a session into a directory.
+    ///
+    /// Screenshots and videos captured by the agent are decoded and written
+    /// as `{activity_id}-{index}.{ext}`, where `index` is the position of the
+    /// artifact within its activity. File names are therefore stable across
+    /// repeated downloads, and existing files are overwritten. Characters
+    /// other than ASCII letters, digits, `-` and `_` in the activity ID are
+    /// replaced with `_`, so a file is never written outside `dir`. The
+    /// directory is created if it does not exist.
+    ///
+    /// Files are written with `std::fs`, which blocks the executor thread
+    /// while each file is written.
+    ///
+    /// # Arguments
+    ///
+    /// * `session_name` - The full resource name of the session.
+    /// * `dir` - The directory to write the media files into.
+    ///
+    /// # Returns
+    ///
+    /// The paths of the written files, in activity order.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use jules_rs::JulesClient;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = JulesClient::new("TOKEN")?;
+    /// let files = client
+    ///     .download_session_media("sessions/abc123", "screenshots")
+    ///     .await?;
+    /// println!("Saved {} files", files.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(session = session_name)))]
+    pub async fn download_session_media(
+        &self,
+        session_name: &str,
+        dir: impl AsRef<Path>,
+    ) -> Result<Vec<PathBuf>> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+
+        let mut written = Vec::new();
+        let mut activities = self.stream_activities(session_name);
+        while let Some(activity) = activities.next().await {
+            let activity = activity?;
+            let artifacts = activity.artifacts.unwrap_or_default();
+            for (index, artifact) in artifacts.iter().enumerate() {
+                let Some(media) = &artifact.media else {
+                    continue;
+                };
+                let ext = media.file_extension().unwrap_or("bin");
+                let path = dir.join(format!("{}-{}.{}", file_stem(&activity.id), index, ext));
+                std::fs::write(&path, media.bytes()?)?;
+                written.push(path);
+            }
         }
-        self.execute(rb).await
+        Ok(written)
+    }
+
+    /// Collects every command the agent executed during a session.
+    ///
+    /// The returned [`CommandLog`] lists the commands in order, flags those
+    /// with a non-zero exit code, and includes pass/fail counts for any test
+    /// runner output it recognises.
+    ///
+    /// # Arguments
+    ///
+    /// * `session_name` - The full resource name of the session.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use jules_rs::JulesClient;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = JulesClient::new("TOKEN")?;
+    /// let log = client.session_commands("sessions/abc123").await?;
+    ///
+    /// for failure in log.failures() {
+    ///     println!("`{}` exited with {}", failure.command, failure.exit_code);
+    /// }
+    /// match log.last_test_summary() {
+    ///     Some(tests) => println!("{} passed, {} failed", tests.passed, tests.failed),
+    ///     None => println!("The agent never ran the test suite"),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(session = session_name)))]
+    pub async fn session_commands(&self, session_name: &str) -> Result<CommandLog> {
+        let activities = self.collect_activities(session_name).await?;
+        Ok(CommandLog::from_activities(&activities))
+    }
+
+    /// Builds a [`Transcript`] of a session and all of its activities.
+    ///
+    /// # Arguments
+    ///
+    /// * `session_name` - The full resource name of the session.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(session = session_name)))]
+    pub async fn transcript(&self, session_name: &str) -> Result<Transcript> {
+        let session = self.get_session(session_name).await?;
+        let activities = self.collect_activities(session_name).await?;
+        Ok(Transcript::new(&session, &activities))
+    }
+
+    /// Exports a session and all of its activities as a readable transcript.
+    ///
+    /// # Arguments
+    ///
+    /// * `session_name` - The full resource name of the session.
+    /// * `format` - The output format.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use jules_rs::JulesClient;
+    /// use jules_rs::export::ExportFormat;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = JulesClient::new("TOKEN")?;
+    /// let html = client
+    ///     .export_session("sessions/abc123", ExportFormat::Html)
+    ///     .await?;
+    /// std::fs::write("session.html", html)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(session = session_name, ?format)))]
+    pub async fn export_session(&self, session_name: &str, format: ExportFormat) -> Result<String> {
+        self.transcript(session_name).await?.render(format)
+    }
+
+    /// Gets the most recent code changes produced by a session.
+    ///
+    /// This is the last [`GitPatch`] found in the session's activities, which
+    /// for a finished session holds its final changes.
+    ///
+    /// # Arguments
+    ///
+    /// * `session_name` - The full resource name of the session.
+    ///
+    /// # Returns
+    ///
+    /// The patch, or `None` if the session has not produced any changes.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(session = session_name)))]
+    pub async fn latest_patch(&self, session_name: &str) -> Result<Option<GitPatch>> {
+        let mut activities = self.collect_activities(session_name).await?;
+        activities.sort_by_key(|a| a.create_time);
+        Ok(activities
+            .iter()
+            .rev()
+            .find_map(|a| a.git_patches().last())
+            .cloned())
+    }
+
+    async fn collect_activities(&self, session_name: &str) -> Result<Vec<Activity>> {
+        self.stream_activities(session_name)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect()
     }
 
     // --- Sources API ---
@@ -303,7 +753,8 @@ impl JulesClient {
     ///
     /// * `name` - The full resource name (e.g., `sources/abc123`).
     pub async fn get_source(&self, name: &str) -> Result<Source> {
-        self.execute(self.request(Method::GET, name)).await
+        let rb = self.request(Method::GET, name);
+        self.execute(Call::new("get_source"), rb).await
     }
 
     /// Lists available sources (connected repositories) with pagination.
@@ -318,17 +769,144 @@ impl JulesClient {
         filter: Option<String>,
         page_size: Option<i32>,
         page_token: Option<String>,
+    ) -> Result<ListSourcesResponse> {
+        self.list_sources_page(filter, page_size, page_token, None)
+            .await
+    }
+
+    async fn list_sources_page(
+        &self,
+        filter: Option<String>,
+        page_size: Option<i32>,
+        page_token: Option<String>,
+        page: Option<usize>,
     ) -> Result<ListSourcesResponse> {
         let mut rb = self.request(Method::GET, "sources");
         if let Some(f) = filter {
-            rb = rb.query(&[("filter", f)]);
+            rb = rb.query("filter", f);
         }
         if let Some(ps) = page_size {
-            rb = rb.query(&[("pageSize", ps)]);
+            rb = rb.query("pageSize", ps);
         }
         if let Some(pt) = page_token {
-            rb = rb.query(&[("pageToken", pt)]);
+            rb = rb.query("pageToken", pt);
+        }
+        self.execute(Call::new("list_sources").page(page), rb).await
+    }
+
+    /// Returns an async stream over all sources.
+    ///
+    /// This method automatically handles pagination, yielding sources one at
+    /// a time until all sources have been retrieved.
+    pub fn stream_sources(&self) -> Pin<Box<dyn Stream<Item = Result<Source>> + '_>> {
+        self.stream_sources_filtered(None)
+    }
+
+    /// Returns an async stream over the sources matching `filter`, or all
+    /// sources if it is `None`.
+    ///
+    /// See [`list_sources`](Self::list_sources) for the filter syntax.
+    pub fn stream_sources_filtered(
+        &self,
+        filter: Option<String>,
+    ) -> Pin<Box<dyn Stream<Item = Result<Source>> + '_>> {
+        paginate(move |token, page| {
+            let filter = filter.clone();
+            async move {
+                let resp = self
+                    .list_sources_page(filter, Some(100), token, Some(page))
+                    .await?;
+                Ok((resp.sources, resp.next_page_token))
+            }
+        })
+    }
+
+    /// Finds the source for a GitHub repository.
+    ///
+    /// Sources are listed and compared by [`GitHubRepo`] owner and repository
+    /// name, ignoring case as GitHub does.
+    ///
+    /// # Returns
+    ///
+    /// The matching source, or `None` if the repository is not connected.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use jules_rs::JulesClient;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = JulesClient::new("TOKEN")?;
+    /// if let Some(source) = client.find_github_source("acme", "widgets").await? {
+    ///     println!("{}", source.name);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub async fn find_github_source(&self, owner: &str, repo: &str) -> Result<Option<Source>> {
+        let mut sources = self.stream_sources();
+        while let Some(source) = sources.next().await {
+            let source = source?;
+            let matches = source.github_repo.as_ref().is_some_and(|gh| {
+                gh.owner.eq_ignore_ascii_case(owner) && gh.repo.eq_ignore_ascii_case(repo)
+            });
+            if matches {
+                return Ok(Some(source));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Finds the source a pull request was opened against.
+    ///
+    /// Returns `Ok(None)` if the pull request URL is not a GitHub pull
+    /// request URL or its repository is not connected to Jules.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all, fields(url = %pr.url)))]
+    pub async fn pull_request_source(&self, pr: &PullRequest) -> Result<Option<Source>> {
+        match (pr.owner(), pr.repo()) {
+            (Some(owner), Some(repo)) => self.find_github_source(owner, repo).await,
+            _ => Ok(None),
         }
-        self.execute(rb).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_stem_cannot_escape_the_directory() {
+        assert_eq!(file_stem("act-1_a"), "act-1_a");
+        assert_eq!(file_stem("../../etc/passwd"), "______etc_passwd");
+        assert_eq!(file_stem("a\\b"), "a_b");
+    }
+
+    #[tokio::test]
+    async fn paginate_detects_token_cycles() {
+        // Tokens "a" -> "b" -> "a": not an immediate repeat.
+        let pages: Vec<_> = paginate(|token: Option<String>, page| async move {
+            let next = match token.as_deref() {
+                None | Some("b") => "a",
+                _ => "b",
+            };
+            Ok((vec![page], Some(next.to_string())))
+        })
+        .collect()
+        .await;
+        assert!(matches!(pages[..3], [Ok(1), Ok(2), Ok(3)]));
+        assert!(matches!(&pages[3], Err(JulesError::PageTokenLoop(t)) if t == "a"));
+        assert_eq!(pages.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn paginate_stops_at_empty_token() {
+        let pages: Vec<_> = paginate(|token: Option<String>, page| async move {
+            let next = if token.is_none() { Some("x") } else { Some("") };
+            Ok((vec![page], next.map(str::to_string)))
+        })
+        .collect()
+        .await;
+        assert!(matches!(pages[..], [Ok(1), Ok(2)]));
     }
 }
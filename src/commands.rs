@@ -0,0 +1,337 @@
+//! Extraction and summarisation of the commands run by the agent.
+//!
+//! Every [`BashOutput`] artifact records a command the agent executed, its
+//! combined output, and its exit code. This module collects those artifacts
+//! into a [`CommandLog`] and recognises the summaries printed by common test
+//! runners, so callers can check whether a test suite was actually run (and
+//! passed) before trusting a session's changes.
+
+use crate::models::{Activity, BashOutput};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A test runner whose summary output can be recognised.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TestRunner {
+    /// `cargo test` (one `test result:` line per test binary).
+    Cargo,
+    /// `pytest`.
+    Pytest,
+    /// `jest` (and runners sharing its `Tests:` summary line, such as Vitest).
+    Jest,
+}
+
+/// Pass/fail counts parsed from a test runner's summary output.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct TestSummary {
+    /// The runner that produced the summary.
+    pub runner: TestRunner,
+    /// Number of passing tests.
+    pub passed: u32,
+    /// Number of failing tests (including errors).
+    pub failed: u32,
+    /// Number of skipped or ignored tests.
+    pub skipped: u32,
+}
+
+impl TestSummary {
+    /// Returns `true` if no test failed.
+    pub fn is_success(&self) -> bool {
+        self.failed == 0
+    }
+
+    /// Returns the total number of tests counted in the summary.
+    pub fn total(&self) -> u32 {
+        self.passed
+            .saturating_add(self.failed)
+            .saturating_add(self.skipped)
+    }
+
+    /// Parses a test summary from command output.
+    ///
+    /// Recognises `cargo test`, `pytest` and `jest` summaries. When a
+    /// `cargo test` run prints several `test result:` lines, their counts are
+    /// added up. Returns `None` if no known summary is found.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use jules_rs::{TestRunner, TestSummary};
+    ///
+    /// let output = "running 3 tests\n\
+    ///               test result: FAILED. 2 passed; 1 failed; 0 ignored; 0 measured";
+    /// let summary = TestSummary::parse(output).unwrap();
+    /// assert_eq!(summary.runner, TestRunner::Cargo);
+    /// assert_eq!((summary.passed, summary.failed), (2, 1));
+    /// ```
+    pub fn parse(output: &str) -> Option<Self> {
+        parse_cargo(output)
+            .or_else(|| parse_jest(output))
+            .or_else(|| parse_pytest(output))
+    }
+}
+
+impl BashOutput {
+    /// Returns `true` if the command exited with status `0`.
+    pub fn is_success(&self) -> bool {
+        self.exit_code == 0
+    }
+
+    /// Parses a test runner summary from the command's output.
+    ///
+    /// See [`TestSummary::parse`].
+    pub fn test_summary(&self) -> Option<TestSummary> {
+        TestSummary::parse(&self.output)
+    }
+}
+
+/// A single command executed by the agent.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CommandRecord {
+    /// ID of the activity that produced the command output.
+    pub activity_id: String,
+    /// When the producing activity was created.
+    pub create_time: DateTime<Utc>,
+    /// The command that was executed.
+    pub command: String,
+    /// Combined stdout and stderr output.
+    pub output: String,
+    /// Exit code of the command.
+    pub exit_code: i32,
+    /// Test runner summary found in the output, if any.
+    pub test_summary: Option<TestSummary>,
+}
+
+impl CommandRecord {
+    /// Returns `true` if the command exited with a non-zero status.
+    pub fn failed(&self) -> bool {
+        self.exit_code != 0
+    }
+}
+
+/// Every command the agent executed during a session, in order.
+///
+/// Obtain one from a session with
+/// [`JulesClient::session_commands`](crate::JulesClient::session_commands),
+/// or build one from already-fetched activities with
+/// [`CommandLog::from_activities`].
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CommandLog {
+    /// The executed commands, in activity order.
+    pub commands: Vec<CommandRecord>,
+}
+
+impl CommandLog {
+    /// Builds a command log from a session's activities.
+    ///
+    /// Commands are ordered by activity creation time; commands from the
+    /// same activity keep their artifact order.
+    pub fn from_activities<'a>(activities: impl IntoIterator<Item = &'a Activity>) -> Self {
+        let mut commands = Vec::new();
+        for activity in activities {
+            let Some(artifacts) = &activity.artifacts else {
+                continue;
+            };
+            for bash in artifacts.iter().filter_map(|a| a.bash_output.as_ref()) {
+                commands.push(CommandRecord {
+                    activity_id: activity.id.clone(),
+                    create_time: activity.create_time,
+                    command: bash.command.clone(),
+                    output: bash.output.clone(),
+                    exit_code: bash.exit_code,
+                    test_summary: bash.test_summary(),
+                });
+            }
+        }
+        commands.sort_by_key(|c| c.create_time);
+        Self { commands }
+    }
+
+    /// Returns the commands that exited with a non-zero status.
+    pub fn failures(&self) -> impl Iterator<Item = &CommandRecord> {
+        self.commands.iter().filter(|c| c.failed())
+    }
+
+    /// Returns the commands whose output contained a test runner summary.
+    pub fn test_runs(&self) -> impl Iterator<Item = &CommandRecord> {
+        self.commands.iter().filter(|c| c.test_summary.is_some())
+    }
+
+    /// Returns `true` if the agent ran at least one recognised test suite.
+    pub fn ran_tests(&self) -> bool {
+        self.test_runs().next().is_some()
+    }
+
+    /// Returns the summary of the most recent test run, if any.
+    ///
+    /// This is usually the best indication of the state the agent left the
+    /// code in, since earlier runs may have failed before a fix was applied.
+    pub fn last_test_summary(&self) -> Option<TestSummary> {
+        self.commands.iter().rev().find_map(|c| c.test_summary)
+    }
+}
+
+fn parse_cargo(output: &str) -> Option<TestSummary> {
+    let mut summary: Option<TestSummary> = None;
+    for line in output.lines() {
+        let Some(rest) = line.trim().strip_prefix("test result:") else {
+            continue;
+        };
+        let counts = count_pairs(rest, ';');
+        let s = summary.get_or_insert(TestSummary {
+            runner: TestRunner::Cargo,
+            passed: 0,
+            failed: 0,
+            skipped: 0,
+        });
+        s.passed = s.passed.saturating_add(counts.passed);
+        s.failed = s.failed.saturating_add(counts.failed);
+        s.skipped = s.skipped.saturating_add(counts.skipped);
+    }
+    summary
+}
+
+fn parse_jest(output: &str) -> Option<TestSummary> {
+    let line = output
+        .lines()
+        .rev()
+        .find_map(|line| line.trim().strip_prefix("Tests:"))?;
+    let counts = count_pairs(line, ',');
+    Some(TestSummary {
+        runner: TestRunner::Jest,
+        ..counts
+    })
+}
+
+fn parse_pytest(output: &str) -> Option<TestSummary> {
+    output.lines().rev().find_map(|line| {
+        let line = line.trim().trim_matches('=').trim();
+        // pytest ends its summary with the duration, e.g. "2 passed in 0.12s".
+        let (counts, duration) = line.rsplit_once(" in ")?;
+        if !duration.trim().starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+        let counts = count_pairs(counts, ',');
+        (counts.total() > 0).then_some(TestSummary {
+            runner: TestRunner::Pytest,
+            ..counts
+        })
+    })
+}
+
+/// Sums `"<n> <label>"` pairs such as `"3 passed; 1 failed"`.
+fn count_pairs(text: &str, separator: char) -> TestSummary {
+    let mut summary = TestSummary {
+        runner: TestRunner::Cargo,
+        passed: 0,
+        failed: 0,
+        skipped: 0,
+    };
+    for part in text.split(separator) {
+        let mut words = part.split_whitespace();
+        let (Some(number), Some(label)) = (words.next(), words.next()) else {
+            continue;
+        };
+        // cargo prefixes the first count with the overall result ("ok. 3").
+        let (number, label) = match number.parse::<u32>() {
+            Ok(n) => (n, label),
+            Err(_) => match (label.parse::<u32>(), words.next()) {
+                (Ok(n), Some(label)) => (n, label),
+                _ => continue,
+            },
+        };
+        let count = match label.trim_end_matches([',', '.']) {
+            "passed" => &mut summary.passed,
+            "failed" | "error" | "errors" => &mut summary.failed,
+            "skipped" | "ignored" | "todo" | "xfailed" => &mut summary.skipped,
+            _ => continue,
+        };
+        *count = count.saturating_add(number);
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(runner: TestRunner, passed: u32, failed: u32, skipped: u32) -> TestSummary {
+        TestSummary {
+            runner,
+            passed,
+            failed,
+            skipped,
+        }
+    }
+
+    #[test]
+    fn cargo_adds_up_every_test_binary() {
+        let output = "\
+running 3 tests
+test result: ok. 3 passed; 0 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.01s
+
+running 2 tests
+test result: FAILED. 1 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+";
+        assert_eq!(
+            TestSummary::parse(output),
+            Some(summary(TestRunner::Cargo, 4, 1, 1))
+        );
+    }
+
+    #[test]
+    fn pytest_counts_errors_as_failures_and_xfailed_as_skipped() {
+        let output = "\
+tests/test_app.py ..F.xE
+===== 4 passed, 1 failed, 2 errors, 3 skipped, 1 xfailed in 0.52s =====
+";
+        assert_eq!(
+            TestSummary::parse(output),
+            Some(summary(TestRunner::Pytest, 4, 3, 4))
+        );
+    }
+
+    #[test]
+    fn pytest_single_error() {
+        let output = "=== 1 error in 0.10s ===";
+        assert_eq!(
+            TestSummary::parse(output),
+            Some(summary(TestRunner::Pytest, 0, 1, 0))
+        );
+    }
+
+    #[test]
+    fn pytest_ignores_lines_that_only_mention_a_duration() {
+        assert_eq!(TestSummary::parse("finished in 2s"), None);
+    }
+
+    #[test]
+    fn jest_uses_the_tests_line() {
+        let output = "\
+Test Suites: 1 failed, 3 passed, 4 total
+Tests:       1 failed, 2 skipped, 1 todo, 10 passed, 14 total
+Snapshots:   0 total
+";
+        assert_eq!(
+            TestSummary::parse(output),
+            Some(summary(TestRunner::Jest, 10, 1, 3))
+        );
+    }
+
+    #[test]
+    fn huge_counts_saturate() {
+        let output = "\
+test result: ok. 4294967295 passed; 0 failed; 0 ignored
+test result: ok. 4294967295 passed; 0 failed; 4294967295 ignored
+";
+        let parsed = TestSummary::parse(output).unwrap();
+        assert_eq!(parsed.passed, u32::MAX);
+        assert_eq!(parsed.total(), u32::MAX);
+    }
+
+    #[test]
+    fn unrecognised_output() {
+        assert_eq!(TestSummary::parse("Compiling foo v0.1.0"), None);
+    }
+}
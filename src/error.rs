@@ -12,8 +12,11 @@ use thiserror::Error;
 #[derive(Debug, Error)]
 pub enum JulesError {
     /// An HTTP request failed due to network or connection issues.
+    ///
+    /// The source is the transport's error, e.g. a `reqwest::Error` with the
+    /// default transport.
     #[error("HTTP request failed: {0}")]
-    Http(#[from] reqwest::Error),
+    Http(#[source] Box<dyn std::error::Error + Send + Sync>),
 
     /// Failed to serialize or deserialize JSON data.
     #[error("Serialization error: {0}")]
@@ -25,7 +28,7 @@ pub enum JulesError {
     #[error("API Error (Status: {status}): {message}")]
     Api {
         /// The HTTP status code returned by the API.
-        status: reqwest::StatusCode,
+        status: http::StatusCode,
         /// The error message from the API response body.
         message: String,
     },
@@ -34,6 +37,86 @@ pub enum JulesError {
     #[error("URL parsing error: {0}")]
     Url(#[from] url::ParseError),
 
+    /// Failed to decode base64-encoded data, such as a [`Media`](crate::Media) payload.
+    #[error("Decoding error: {0}")]
+    Decode(#[from] base64::DecodeError),
+
+    /// A filesystem operation failed.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// A local session store operation failed.
+    ///
+    /// With the `store` feature the source is a `rusqlite::Error`. The
+    /// variant exists without the feature so that enabling it does not
+    /// break exhaustive matches.
+    #[error("Store error: {0}")]
+    Store(#[source] Box<dyn std::error::Error + Send + Sync>),
+
+    /// The API returned a next page token it had already returned, which
+    /// would make pagination loop forever.
+    #[error("Pagination loop: the API returned page token {0:?} again")]
+    PageTokenLoop(String),
+
+    /// A cassette has no recorded response for a request.
+    ///
+    /// Cassettes are replayed with the `mock` feature. The variant exists
+    /// without it so that enabling the feature does not break exhaustive
+    /// matches.
+    #[error("Cassette error: {0}")]
+    Cassette(String),
+
+    /// The client has no HTTP transport: the `reqwest` feature is disabled
+    /// and [`JulesClient::with_transport`](crate::JulesClient::with_transport)
+    /// was not called.
+    #[error(
+        "No HTTP transport: enable the `reqwest` feature or call `JulesClient::with_transport`"
+    )]
+    NoTransport,
+
+    /// A polling helper needs to wait, but the client has no timer: the
+    /// `tokio-runtime` feature is disabled and
+    /// [`JulesClient::with_sleep`](crate::JulesClient::with_sleep) was not
+    /// called.
+    #[error("No timer: enable the `tokio-runtime` feature or call `JulesClient::with_sleep`")]
+    NoSleep,
+
+    /// A middleware or tower layer failed.
+    #[error("Middleware error: {0}")]
+    Middleware(#[source] Box<dyn std::error::Error + Send + Sync>),
+
+    /// A batch manifest could not be read or contains an invalid entry.
+    #[error("Invalid manifest: {0}")]
+    InvalidManifest(String),
+
+    /// A prompt template is malformed or could not be rendered.
+    #[error("Template error: {0}")]
+    Template(String),
+
+    /// The GitHub API returned an error response while looking up a pull
+    /// request.
+    #[error("GitHub API error (Status: {status}): {message}")]
+    GitHub {
+        /// The HTTP status code returned by GitHub.
+        status: http::StatusCode,
+        /// The error message from the response body.
+        message: String,
+    },
+
+    /// A pull request comes from a fork, so its branch does not exist in the
+    /// repository a follow-up session would start from.
+    #[error("Pull request {url} comes from the fork {head_repo}")]
+    ForkPullRequest {
+        /// URL of the pull request.
+        url: String,
+        /// Full name (`owner/repo`) of the repository the branch lives in.
+        head_repo: String,
+    },
+
+    /// A session has not created a pull request to continue from.
+    #[error("Session {0} has not created a pull request")]
+    NoPullRequest(String),
+
     /// An invalid resource name was provided.
     ///
     /// Resource names must follow the format `resource_type/resource_id`.
@@ -41,6 +124,26 @@ pub enum JulesError {
     InvalidResourceName(String),
 }
 
+#[cfg(feature = "reqwest")]
+impl From<reqwest::Error> for JulesError {
+    fn from(error: reqwest::Error) -> Self {
+        JulesError::Http(Box::new(error))
+    }
+}
+
+impl From<http::Error> for JulesError {
+    fn from(error: http::Error) -> Self {
+        JulesError::Http(Box::new(error))
+    }
+}
+
+#[cfg(feature = "store")]
+impl From<rusqlite::Error> for JulesError {
+    fn from(error: rusqlite::Error) -> Self {
+        JulesError::Store(Box::new(error))
+    }
+}
+
 /// A specialized [`Result`](std::result::Result) type for Jules API operations.
 ///
 /// This type alias provides a convenient way to return results that may fail
@@ -0,0 +1,790 @@
+//! Export of sessions as readable transcripts.
+//!
+//! A [`Transcript`] captures a session and all of its activities: user and
+//! agent messages, generated plans, progress updates, command output, code
+//! changes, and media. It can be rendered as Markdown, as a self-contained
+//! HTML page, or serialized as JSON.
+//!
+//! The JSON form is the [`Transcript`] type itself. Its layout is versioned by
+//! [`Transcript::schema_version`] and does not follow changes to the API
+//! models, so it is suitable for archiving.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use jules_rs::JulesClient;
+//! use jules_rs::export::ExportFormat;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = JulesClient::new("TOKEN")?;
+//! let transcript = client.transcript("sessions/abc123").await?;
+//!
+//! std::fs::write("session.md", transcript.render(ExportFormat::Markdown)?)?;
+//! std::fs::write("session.html", transcript.to_html())?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::Result;
+use crate::models::{Activity, Session};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+/// The current version of the [`Transcript`] JSON schema.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// An output format for a [`Transcript`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// GitHub-flavored Markdown.
+    Markdown,
+    /// A self-contained HTML page with inline styles and images.
+    Html,
+    /// Pretty-printed JSON following the [`Transcript`] schema.
+    Json,
+}
+
+impl ExportFormat {
+    /// Returns the conventional file extension for the format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Html => "html",
+            Self::Json => "json",
+        }
+    }
+}
+
+/// A session and its activities, in a stable, render-ready form.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Transcript {
+    /// Version of the transcript schema, currently [`SCHEMA_VERSION`].
+    pub schema_version: u32,
+    /// Details of the session.
+    pub session: TranscriptSession,
+    /// One entry per activity, in chronological order.
+    pub entries: Vec<TranscriptEntry>,
+}
+
+/// Session details included in a [`Transcript`].
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptSession {
+    /// The full resource name of the session.
+    pub name: Option<String>,
+    /// The session title.
+    pub title: Option<String>,
+    /// The prompt the session was started with.
+    pub prompt: String,
+    /// The source resource name.
+    pub source: String,
+    /// The branch the session started from.
+    pub starting_branch: Option<String>,
+    /// The session state, as reported by the API (e.g. `COMPLETED`).
+    pub state: Option<String>,
+    /// When the session was created.
+    pub create_time: Option<DateTime<Utc>>,
+    /// When the session was last updated.
+    pub update_time: Option<DateTime<Utc>>,
+    /// URL to view the session in the Jules web app.
+    pub url: Option<String>,
+    /// URLs of pull requests created by the session.
+    pub pull_requests: Vec<String>,
+}
+
+/// A single activity in a [`Transcript`].
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptEntry {
+    /// The activity ID.
+    pub id: String,
+    /// When the activity was created.
+    pub create_time: DateTime<Utc>,
+    /// Who originated the activity (`user`, `agent`, or `system`).
+    pub originator: String,
+    /// The activity description, if any.
+    pub description: Option<String>,
+    /// The event the activity records, if any.
+    pub event: Option<TranscriptEvent>,
+    /// Artifacts attached to the activity.
+    pub artifacts: Vec<TranscriptArtifact>,
+}
+
+/// The event recorded by a [`TranscriptEntry`].
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum TranscriptEvent {
+    /// The agent posted a message.
+    AgentMessage {
+        /// The message text.
+        text: String,
+    },
+    /// The user posted a message.
+    UserMessage {
+        /// The message text.
+        text: String,
+    },
+    /// The agent generated a plan.
+    Plan {
+        /// The plan ID.
+        plan_id: String,
+        /// The plan steps, in order.
+        steps: Vec<TranscriptPlanStep>,
+    },
+    /// A plan was approved.
+    PlanApproved {
+        /// ID of the approved plan.
+        plan_id: String,
+    },
+    /// The agent reported progress.
+    Progress {
+        /// Title of the update.
+        title: String,
+        /// Details of the update.
+        description: String,
+    },
+    /// The session completed.
+    SessionCompleted,
+    /// The session failed.
+    SessionFailed {
+        /// The reason for failure.
+        reason: String,
+    },
+}
+
+/// A step of a plan in a [`Transcript`].
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptPlanStep {
+    /// 1-based step number.
+    pub number: u32,
+    /// Title of the step.
+    pub title: String,
+    /// Description of the step.
+    pub description: String,
+}
+
+/// An artifact in a [`Transcript`].
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum TranscriptArtifact {
+    /// A command run by the agent.
+    Bash {
+        /// The command that was executed.
+        command: String,
+        /// Combined stdout and stderr output.
+        output: String,
+        /// Exit code of the command.
+        exit_code: i32,
+    },
+    /// A set of code changes.
+    Diff {
+        /// The source the changes apply to.
+        source: String,
+        /// The base commit, if known.
+        base_commit_id: Option<String>,
+        /// The suggested commit message, if any.
+        suggested_commit_message: Option<String>,
+        /// The changes in unified diff format.
+        patch: String,
+    },
+    /// An image, video, or other media file.
+    Media {
+        /// MIME type of the media.
+        mime_type: String,
+        /// Base64-encoded media data.
+        data: String,
+    },
+}
+
+impl Transcript {
+    /// Builds a transcript from a session and its activities.
+    ///
+    /// Activities are sorted by creation time.
+    pub fn new<'a>(session: &Session, activities: impl IntoIterator<Item = &'a Activity>) -> Self {
+        let mut entries: Vec<TranscriptEntry> = activities.into_iter().map(entry).collect();
+        entries.sort_by_key(|e| e.create_time);
+        Self {
+            schema_version: SCHEMA_VERSION,
+            session: TranscriptSession {
+                name: session.name.clone(),
+                title: session.title.clone(),
+                prompt: session.prompt.clone(),
+                source: session.source_context.source.clone(),
+                starting_branch: session
+                    .source_context
+                    .github_repo_context
+                    .as_ref()
+                    .map(|c| c.starting_branch.clone()),
+                state: session.state.as_ref().map(|s| s.as_str().to_string()),
+                create_time: session.create_time,
+                update_time: session.update_time,
+                url: session.url.clone(),
+                pull_requests: session.pull_requests().map(|pr| pr.url.clone()).collect(),
+            },
+            entries,
+        }
+    }
+
+    /// Renders the transcript in the given format.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error only if JSON serialization fails.
+    pub fn render(&self, format: ExportFormat) -> Result<String> {
+        match format {
+            ExportFormat::Markdown => Ok(self.to_markdown()),
+            ExportFormat::Html => Ok(self.to_html()),
+            ExportFormat::Json => self.to_json(),
+        }
+    }
+
+    /// Serializes the transcript as pretty-printed JSON.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Renders the transcript as Markdown.
+    ///
+    /// Images are embedded as `data:` URIs so the document has no external
+    /// references.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let s = &self.session;
+        let _ = writeln!(
+            out,
+            "# {}\n",
+            s.title.as_deref().unwrap_or("Untitled session")
+        );
+        for (label, value) in self.details() {
+            let _ = writeln!(out, "- **{}:** {}", label, value);
+        }
+        for url in &s.pull_requests {
+            let _ = writeln!(out, "- **Pull request:** {}", url);
+        }
+        out.push_str("\n## Prompt\n\n");
+        for line in s.prompt.lines() {
+            let _ = writeln!(out, "> {}", line);
+        }
+        out.push_str("\n## Activity\n\n");
+
+        for e in &self.entries {
+            let _ = writeln!(
+                out,
+                "### {} · {}\n",
+                heading(e),
+                e.create_time.format("%Y-%m-%d %H:%M:%S UTC")
+            );
+            match &e.event {
+                Some(TranscriptEvent::AgentMessage { text })
+                | Some(TranscriptEvent::UserMessage { text }) => {
+                    let _ = writeln!(out, "{}\n", text);
+                }
+                Some(TranscriptEvent::Plan { steps, .. }) => {
+                    for step in steps {
+                        let _ = writeln!(out, "{}. **{}**", step.number, step.title);
+                        if !step.description.is_empty() {
+                            let _ = writeln!(out, "   {}", step.description);
+                        }
+                    }
+                    out.push('\n');
+                }
+                Some(TranscriptEvent::PlanApproved { plan_id }) => {
+                    let _ = writeln!(out, "Plan `{}` approved.\n", plan_id);
+                }
+                Some(TranscriptEvent::Progress { title, description }) => {
+                    let _ = writeln!(out, "**{}**\n", title);
+                    if !description.is_empty() {
+                        let _ = writeln!(out, "{}\n", description);
+                    }
+                }
+                Some(TranscriptEvent::SessionCompleted) => {
+                    out.push_str("Session completed.\n\n");
+                }
+                Some(TranscriptEvent::SessionFailed { reason }) => {
+                    let _ = writeln!(out, "Session failed: {}\n", reason);
+                }
+                None => {
+                    if let Some(d) = &e.description {
+                        let _ = writeln!(out, "{}\n", d);
+                    }
+                }
+            }
+            for artifact in &e.artifacts {
+                match artifact {
+                    TranscriptArtifact::Bash {
+                        command,
+                        output,
+                        exit_code,
+                    } => {
+                        let fence = fence_for(output);
+                        let _ = writeln!(out, "{fence}console\n$ {}", command);
+                        if !output.is_empty() {
+                            let _ = writeln!(out, "{}", output.trim_end());
+                        }
+                        let _ = writeln!(out, "{fence}\n\nExit code: `{}`\n", exit_code);
+                    }
+                    TranscriptArtifact::Diff {
+                        suggested_commit_message,
+                        patch,
+                        ..
+                    } => {
+                        if let Some(msg) = suggested_commit_message {
+                            let _ = writeln!(out, "Suggested commit message: *{}*\n", msg.trim());
+                        }
+                        let fence = fence_for(patch);
+                        let _ = writeln!(out, "{fence}diff\n{}\n{fence}\n", patch.trim_end());
+                    }
+                    TranscriptArtifact::Media { mime_type, data } => {
+                        let _ = writeln!(
+                            out,
+                            "![{}](data:{};base64,{})\n",
+                            mime_type, mime_type, data
+                        );
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    /// Renders the transcript as a self-contained HTML page.
+    ///
+    /// Styles are inlined and images are embedded as `data:` URIs, so the
+    /// page can be attached to a review or opened offline.
+    pub fn to_html(&self) -> String {
+        let s = &self.session;
+        let title = escape(s.title.as_deref().unwrap_or("Untitled session"));
+        let mut out = String::new();
+        let _ = write!(
+            out,
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>{title}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>\n<dl>\n"
+        );
+        for (label, value) in self.details() {
+            let _ = writeln!(out, "<dt>{}</dt><dd>{}</dd>", label, escape(&value));
+        }
+        for url in &s.pull_requests {
+            // Only web URLs become links; anything else, such as a
+            // `javascript:` URL, is shown as text.
+            let text = escape(url);
+            if is_web_url(url) {
+                let _ = writeln!(
+                    out,
+                    "<dt>Pull request</dt><dd><a href=\"{text}\">{text}</a></dd>"
+                );
+            } else {
+                let _ = writeln!(out, "<dt>Pull request</dt><dd>{text}</dd>");
+            }
+        }
+        let _ = writeln!(
+            out,
+            "</dl>\n<h2>Prompt</h2>\n<blockquote>{}</blockquote>\n<h2>Activity</h2>",
+            escape(&s.prompt)
+        );
+
+        for e in &self.entries {
+            let _ = writeln!(
+                out,
+                "<section class=\"entry {}\">\n<h3>{} <time>{}</time></h3>",
+                escape(&e.originator),
+                heading(e),
+                e.create_time.format("%Y-%m-%d %H:%M:%S UTC")
+            );
+            match &e.event {
+                Some(TranscriptEvent::AgentMessage { text })
+                | Some(TranscriptEvent::UserMessage { text }) => {
+                    let _ = writeln!(out, "<p class=\"message\">{}</p>", escape(text));
+                }
+                Some(TranscriptEvent::Plan { steps, .. }) => {
+                    out.push_str("<ol>\n");
+                    for step in steps {
+                        let _ = writeln!(
+                            out,
+                            "<li><strong>{}</strong><br>{}</li>",
+                            escape(&step.title),
+                            escape(&step.description)
+                        );
+                    }
+                    out.push_str("</ol>\n");
+                }
+                Some(TranscriptEvent::PlanApproved { plan_id }) => {
+                    let _ = writeln!(
+                        out,
+                        "<p>Plan <code>{}</code> approved.</p>",
+                        escape(plan_id)
+                    );
+                }
+                Some(TranscriptEvent::Progress { title, description }) => {
+                    let _ = writeln!(
+                        out,
+                        "<p><strong>{}</strong></p>\n<p>{}</p>",
+                        escape(title),
+                        escape(description)
+                    );
+                }
+                Some(TranscriptEvent::SessionCompleted) => {
+                    out.push_str("<p>Session completed.</p>\n");
+                }
+                Some(TranscriptEvent::SessionFailed { reason }) => {
+                    let _ = writeln!(
+                        out,
+                        "<p class=\"failed\">Session failed: {}</p>",
+                        escape(reason)
+                    );
+                }
+                None => {
+                    if let Some(d) = &e.description {
+                        let _ = writeln!(out, "<p>{}</p>", escape(d));
+                    }
+                }
+            }
+            for artifact in &e.artifacts {
+                match artifact {
+                    TranscriptArtifact::Bash {
+                        command,
+                        output,
+                        exit_code,
+                    } => {
+                        let class = if *exit_code == 0 { "ok" } else { "failed" };
+                        let _ = writeln!(
+                            out,
+                            "<pre class=\"bash\"><code>$ {}\n{}</code></pre>\n\
+                             <p class=\"{class}\">Exit code: <code>{}</code></p>",
+                            escape(command),
+                            escape(output.trim_end()),
+                            exit_code
+                        );
+                    }
+                    TranscriptArtifact::Diff {
+                        suggested_commit_message,
+                        patch,
+                        ..
+                    } => {
+                        if let Some(msg) = suggested_commit_message {
+                            let _ = writeln!(
+                                out,
+                                "<p>Suggested commit message: <em>{}</em></p>",
+                                escape(msg.trim())
+                            );
+                        }
+                        out.push_str("<pre class=\"diff\"><code>");
+                        for line in patch.lines() {
+                            let class = match line.as_bytes().first() {
+                                Some(b'+') if !line.starts_with("+++") => "add",
+                                Some(b'-') if !line.starts_with("---") => "del",
+                                Some(b'@') => "hunk",
+                                _ => "ctx",
+                            };
+                            let _ =
+                                writeln!(out, "<span class=\"{class}\">{}</span>", escape(line));
+                        }
+                        out.push_str("</code></pre>\n");
+                    }
+                    TranscriptArtifact::Media { mime_type, data } => {
+                        let mime = escape(mime_type);
+                        if mime_type.starts_with("video/") {
+                            let _ = writeln!(
+                                out,
+                                "<video controls src=\"data:{mime};base64,{}\"></video>",
+                                escape(data)
+                            );
+                        } else {
+                            let _ = writeln!(
+                                out,
+                                "<img alt=\"{mime}\" src=\"data:{mime};base64,{}\">",
+                                escape(data)
+                            );
+                        }
+                    }
+                }
+            }
+            out.push_str("</section>\n");
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+
+    /// Label/value pairs shown in the transcript header.
+    fn details(&self) -> Vec<(&'static str, String)> {
+        let s = &self.session;
+        let mut details = Vec::new();
+        if let Some(name) = &s.name {
+            details.push(("Session", name.clone()));
+        }
+        if let Some(state) = &s.state {
+            details.push(("State", state.clone()));
+        }
+        details.push(("Source", s.source.clone()));
+        if let Some(branch) = &s.starting_branch {
+            details.push(("Branch", branch.clone()));
+        }
+        if let Some(t) = s.create_time {
+            details.push(("Created", t.format("%Y-%m-%d %H:%M:%S UTC").to_string()));
+        }
+        if let Some(t) = s.update_time {
+            details.push(("Updated", t.format("%Y-%m-%d %H:%M:%S UTC").to_string()));
+        }
+        if let Some(url) = &s.url {
+            details.push(("URL", url.clone()));
+        }
+        details
+    }
+}
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;max-width:60rem;margin:2rem auto;padding:0 1rem;color:#1f2328}\
+dl{display:grid;grid-template-columns:max-content 1fr;gap:.25rem 1rem}dt{font-weight:600}dd{margin:0}\
+blockquote{border-left:4px solid #d0d7de;margin:0;padding:0 1rem;white-space:pre-wrap}\
+.entry{border-top:1px solid #d0d7de;padding:.5rem 0}.entry h3 time{font-weight:normal;color:#656d76;font-size:.85em}\
+.entry.user .message{background:#ddf4ff;padding:.5rem;border-radius:6px}.message{white-space:pre-wrap}\
+pre{background:#f6f8fa;padding:.75rem;overflow-x:auto;border-radius:6px}\
+.diff .add{color:#116329;background:#dafbe1}.diff .del{color:#82071e;background:#ffebe9}.diff .hunk{color:#0550ae}\
+.diff span{display:block}.ok{color:#116329}.failed{color:#82071e}img,video{max-width:100%}";
+
+fn entry(activity: &Activity) -> TranscriptEntry {
+    let event = if let Some(m) = &activity.agent_messaged {
+        Some(TranscriptEvent::AgentMessage {
+            text: m.agent_message.clone(),
+        })
+    } else if let Some(m) = &activity.user_messaged {
+        Some(TranscriptEvent::UserMessage {
+            text: m.user_message.clone(),
+        })
+    } else if let Some(p) = &activity.plan_generated {
+        let mut steps = p.plan.steps.clone();
+        steps.sort_by_key(|s| s.index);
+        Some(TranscriptEvent::Plan {
+            plan_id: p.plan.id.clone(),
+            steps: steps
+                .into_iter()
+                .map(|s| TranscriptPlanStep {
+                    number: s.index.max(0) as u32 + 1,
+                    title: s.title,
+                    description: s.description,
+                })
+                .collect(),
+        })
+    } else if let Some(p) = &activity.plan_approved {
+        Some(TranscriptEvent::PlanApproved {
+            plan_id: p.plan_id.clone(),
+        })
+    } else if let Some(p) = &activity.progress_updated {
+        Some(TranscriptEvent::Progress {
+            title: p.title.clone(),
+            description: p.description.clone(),
+        })
+    } else if activity.session_completed.is_some() {
+        Some(TranscriptEvent::SessionCompleted)
+    } else {
+        activity
+            .session_failed
+            .as_ref()
+            .map(|f| TranscriptEvent::SessionFailed {
+                reason: f.reason.clone(),
+            })
+    };
+
+    let mut artifacts = Vec::new();
+    for a in activity.artifacts.iter().flatten() {
+        if let Some(b) = &a.bash_output {
+            artifacts.push(TranscriptArtifact::Bash {
+                command: b.command.clone(),
+                output: b.output.clone(),
+                exit_code: b.exit_code,
+            });
+        }
+        if let Some(c) = &a.change_set {
+            if let Some(p) = &c.git_patch {
+                artifacts.push(TranscriptArtifact::Diff {
+                    source: c.source.clone(),
+                    base_commit_id: Some(p.base_commit_id.clone()).filter(|id| !id.is_empty()),
+                    suggested_commit_message: p.suggested_commit_message.clone(),
+                    patch: p.unidiff_patch.clone(),
+                });
+            }
+        }
+        if let Some(m) = &a.media {
+            artifacts.push(TranscriptArtifact::Media {
+                mime_type: m.mime_type.clone(),
+                data: m.data.clone(),
+            });
+        }
+    }
+
+    TranscriptEntry {
+        id: activity.id.clone(),
+        create_time: activity.create_time,
+        originator: activity.originator.clone(),
+        description: activity.description.clone(),
+        event,
+        artifacts,
+    }
+}
+
+fn heading(entry: &TranscriptEntry) -> &'static str {
+    match &entry.event {
+        Some(TranscriptEvent::AgentMessage { .. }) => "Agent",
+        Some(TranscriptEvent::UserMessage { .. }) => "User",
+        Some(TranscriptEvent::Plan { .. }) => "Plan",
+        Some(TranscriptEvent::PlanApproved { .. }) => "Plan approved",
+        Some(TranscriptEvent::Progress { .. }) => "Progress",
+        Some(TranscriptEvent::SessionCompleted) => "Completed",
+        Some(TranscriptEvent::SessionFailed { .. }) => "Failed",
+        None => match entry.originator.as_str() {
+            "agent" => "Agent",
+            "user" => "User",
+            _ => "System",
+        },
+    }
+}
+
+/// Returns a code fence longer than any backtick run in `content`.
+fn fence_for(content: &str) -> String {
+    let longest = content.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
+/// Returns `true` for `http` and `https` URLs.
+fn is_web_url(url: &str) -> bool {
+    url::Url::parse(url).is_ok_and(|u| matches!(u.scheme(), "http" | "https"))
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn session(pull_request: &str) -> Session {
+        serde_json::from_value(json!({
+            "name": "sessions/1",
+            "title": "Fix <b>bold</b> bug",
+            "prompt": "Fix the bug\nand add a test",
+            "sourceContext": {
+                "source": "sources/github/o/r",
+                "githubRepoContext": { "startingBranch": "main" }
+            },
+            "state": "COMPLETED",
+            "outputs": [{ "pullRequest": { "url": pull_request, "title": "Fix", "description": "" } }]
+        }))
+        .unwrap()
+    }
+
+    fn activities() -> Vec<Activity> {
+        serde_json::from_value(json!([
+            {
+                "name": "sessions/1/activities/b",
+                "id": "b",
+                "createTime": "2025-01-01T00:00:02Z",
+                "originator": "agent",
+                "progressUpdated": { "title": "Ran tests", "description": "" },
+                "artifacts": [{
+                    "bashOutput": { "command": "cargo test", "output": "ok <done>", "exitCode": 0 }
+                }, {
+                    "changeSet": {
+                        "source": "sources/github/o/r",
+                        "gitPatch": {
+                            "unidiffPatch": "--- a/x\n+++ b/x\n@@ -1 +1 @@\n-old\n+new",
+                            "baseCommitId": "abc"
+                        }
+                    }
+                }]
+            },
+            {
+                "name": "sessions/1/activities/a",
+                "id": "a",
+                "createTime": "2025-01-01T00:00:01Z",
+                "originator": "agent",
+                "agentMessaged": { "agentMessage": "Looking at it" }
+            }
+        ]))
+        .unwrap()
+    }
+
+    fn transcript(pull_request: &str) -> Transcript {
+        Transcript::new(&session(pull_request), &activities())
+    }
+
+    #[test]
+    fn entries_are_sorted_by_creation_time() {
+        let t = transcript("https://github.com/o/r/pull/1");
+        let ids: Vec<_> = t.entries.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, ["a", "b"]);
+    }
+
+    #[test]
+    fn markdown() {
+        let md = transcript("https://github.com/o/r/pull/1").to_markdown();
+        assert!(md.starts_with("# Fix <b>bold</b> bug\n"));
+        assert!(md.contains("- **Branch:** main"));
+        assert!(md.contains("- **Pull request:** https://github.com/o/r/pull/1"));
+        assert!(md.contains("> Fix the bug\n> and add a test\n"));
+        assert!(md.contains("$ cargo test"));
+        assert!(md.contains("```diff\n"));
+        assert!(md.find("Looking at it") < md.find("Ran tests"));
+    }
+
+    #[test]
+    fn html_escapes_text() {
+        let html = transcript("https://github.com/o/r/pull/1").to_html();
+        assert!(html.contains("<title>Fix &lt;b&gt;bold&lt;/b&gt; bug</title>"));
+        assert!(html.contains("ok &lt;done&gt;"));
+        assert!(!html.contains("<b>"));
+        assert!(html.contains(
+            "<a href=\"https://github.com/o/r/pull/1\">https://github.com/o/r/pull/1</a>"
+        ));
+    }
+
+    #[test]
+    fn html_does_not_link_other_schemes() {
+        let html = transcript("javascript:alert(1)").to_html();
+        assert!(!html.contains("href=\"javascript:"));
+        assert!(html.contains("<dd>javascript:alert(1)</dd>"));
+    }
+
+    #[test]
+    fn html_escapes_quotes_in_links() {
+        let html = transcript("https://example.com/\"onmouseover=\"alert(1)").to_html();
+        assert!(!html.contains("\"onmouseover"));
+        assert!(html.contains("&quot;onmouseover"));
+    }
+
+    #[test]
+    fn json_round_trips() {
+        let t = transcript("https://github.com/o/r/pull/1");
+        let json = t.render(ExportFormat::Json).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["schemaVersion"], SCHEMA_VERSION);
+        assert_eq!(
+            value["session"]["pullRequests"][0],
+            "https://github.com/o/r/pull/1"
+        );
+        assert_eq!(value["entries"].as_array().unwrap().len(), 2);
+        let back: Transcript = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.entries.len(), 2);
+    }
+}
@@ -31,7 +31,27 @@
 //! - **Activities**: Track session activities and progress updates
 //! - **Sources**: List and query connected GitHub repositories
 //! - **Streaming**: Paginate through results with async streams
+//! - **Querying**: Filter sessions by state, repository, time, and text with [`SessionQuery`]
 //! - **Type-safe**: Full Rust types for all API models
+//! - **Watching**: Follow a session live with [`JulesClient::watch_session`]
+//! - **Orchestration**: Run many sessions under a concurrency limit with retries
+//! - **Local journal**: Mirror sessions into SQLite with the `store` feature
+//! - **Metrics**: Request counts, latency histograms, and session outcomes via
+//!   [`metrics::Metrics`]
+//! - **Tracing**: Spans for every API call and events for session state
+//!   changes with the `tracing` feature
+//! - **Middleware**: Wrap every request with [`middleware::Middleware`], or a
+//!   tower layer stack with the `tower` feature
+//! - **Testing**: Program against the [`api::JulesApi`] trait; an in-memory
+//!   fake, an in-process mock Jules server, and record/replay cassettes are
+//!   available with the `mock` feature
+//! - **Blocking client**: `blocking::JulesClient` for synchronous code with
+//!   the `blocking` feature
+//! - **Runtime-agnostic**: Plug in your own HTTP stack and timer through
+//!   [`transport::HttpTransport`] and [`transport::Sleep`]; `reqwest` is the
+//!   default HTTP stack with the default `reqwest` feature, and the
+//!   `tokio-runtime` feature makes tokio's timer the default
+//! - **Prompt templates**: Share prompts with typed variables and includes via [`template`]
 //!
 //! ## Authentication
 //!
@@ -89,10 +109,40 @@
 //! # }
 //! ```
 
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+pub mod api;
+pub mod batch;
+#[cfg(feature = "blocking")]
+#[cfg_attr(docsrs, doc(cfg(feature = "blocking")))]
+pub mod blocking;
+#[cfg(feature = "mock")]
+#[cfg_attr(docsrs, doc(cfg(feature = "mock")))]
+pub mod cassette;
+pub mod chain;
 pub mod client;
+pub mod commands;
 pub mod error;
+pub mod export;
+pub mod metrics;
+pub mod middleware;
 pub mod models;
+pub mod orchestrator;
+pub mod query;
+pub mod secret;
+#[cfg(feature = "store")]
+#[cfg_attr(docsrs, doc(cfg(feature = "store")))]
+pub mod store;
+pub mod template;
+#[cfg(feature = "mock")]
+#[cfg_attr(docsrs, doc(cfg(feature = "mock")))]
+pub mod testing;
+pub mod transport;
+pub mod watch;
 
 pub use client::JulesClient;
+pub use commands::{CommandLog, CommandRecord, TestRunner, TestSummary};
 pub use error::{JulesError, Result};
 pub use models::*;
+pub use query::SessionQuery;
+pub use secret::Secret;
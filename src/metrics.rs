@@ -0,0 +1,340 @@
+//! Metrics hooks for API calls and session outcomes.
+//!
+//! A [`Metrics`] implementation attached with [`JulesClient::with_metrics`]
+//! is called after every API request with the client method, HTTP status,
+//! latency, and retry count, and whenever [`watch_session`] or the
+//! [`Orchestrator`] sees a session finish. Forward these to the metrics
+//! system of your choice, or collect them with [`InMemoryMetrics`].
+//!
+//! [`watch_session`]: JulesClient::watch_session
+//! [`Orchestrator`]: crate::orchestrator::Orchestrator
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use std::sync::Arc;
+//! use jules_rs::JulesClient;
+//! use jules_rs::metrics::InMemoryMetrics;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let metrics = Arc::new(InMemoryMetrics::new());
+//! let client = JulesClient::new("YOUR_API_KEY")?.with_metrics(metrics.clone());
+//! client.list_sessions(None, None).await?;
+//!
+//! let snapshot = metrics.snapshot();
+//! for (key, count) in &snapshot.requests {
+//!     println!("{} {:?}: {count}", key.method, key.status);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::JulesClient;
+use crate::models::{Session, SessionState};
+use http::StatusCode;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Receives metrics from a [`JulesClient`].
+///
+/// Both methods do nothing by default. They are called synchronously, so
+/// they should return quickly.
+///
+/// Only requests to the Jules API are reported. The GitHub lookups made by
+/// [`continue_from`](JulesClient::continue_from) are not.
+pub trait Metrics: Send + Sync + 'static {
+    /// Called after every API request.
+    fn on_request(&self, request: &RequestMetrics) {
+        let _ = request;
+    }
+
+    /// Called when a session is seen to reach a terminal state.
+    fn on_session_finished(&self, outcome: &SessionOutcome) {
+        let _ = outcome;
+    }
+}
+
+impl<T: Metrics + ?Sized> Metrics for Arc<T> {
+    fn on_request(&self, request: &RequestMetrics) {
+        (**self).on_request(request);
+    }
+
+    fn on_session_finished(&self, outcome: &SessionOutcome) {
+        (**self).on_session_finished(outcome);
+    }
+}
+
+/// A completed API request.
+#[derive(Debug, Clone)]
+pub struct RequestMetrics {
+    /// The client method that made the request, e.g. `get_session`.
+    pub method: &'static str,
+    /// The HTTP status, or `None` if no response was received.
+    pub status: Option<StatusCode>,
+    /// The time from sending the request to reading the response body.
+    pub latency: Duration,
+    /// How many times the request was retried by middleware.
+    pub retries: u32,
+}
+
+/// A session that reached a terminal state.
+#[derive(Debug, Clone)]
+pub struct SessionOutcome {
+    /// The session's resource name.
+    pub session: String,
+    /// The terminal state, `COMPLETED` or `FAILED`.
+    pub state: SessionState,
+    /// The time from the session's creation to its last update, if the API
+    /// reported both.
+    pub duration: Option<Duration>,
+}
+
+impl SessionOutcome {
+    /// Returns the outcome of a session, or `None` if it is not terminal.
+    pub fn of(session: &Session) -> Option<Self> {
+        let state = session.state.clone().filter(SessionState::is_terminal)?;
+        let duration = session
+            .create_time
+            .zip(session.update_time)
+            .and_then(|(created, updated)| (updated - created).to_std().ok());
+        Some(Self {
+            session: session.name.clone().unwrap_or_default(),
+            state,
+            duration,
+        })
+    }
+}
+
+/// Request latency bucket bounds used by [`InMemoryMetrics`].
+pub const LATENCY_BUCKETS: &[Duration] = &[
+    Duration::from_millis(50),
+    Duration::from_millis(100),
+    Duration::from_millis(250),
+    Duration::from_millis(500),
+    Duration::from_secs(1),
+    Duration::from_millis(2500),
+    Duration::from_secs(5),
+    Duration::from_secs(10),
+];
+
+/// Session duration bucket bounds used by [`InMemoryMetrics`].
+pub const SESSION_DURATION_BUCKETS: &[Duration] = &[
+    Duration::from_secs(60),
+    Duration::from_secs(5 * 60),
+    Duration::from_secs(15 * 60),
+    Duration::from_secs(30 * 60),
+    Duration::from_secs(60 * 60),
+    Duration::from_secs(2 * 60 * 60),
+    Duration::from_secs(6 * 60 * 60),
+];
+
+/// A histogram of durations with fixed bucket bounds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Histogram {
+    bounds: Vec<Duration>,
+    counts: Vec<u64>,
+    sum: Duration,
+}
+
+impl Histogram {
+    /// Creates an empty histogram with the given ascending upper bounds.
+    ///
+    /// Values above the last bound are counted in an overflow bucket.
+    pub fn new(bounds: &[Duration]) -> Self {
+        Self {
+            bounds: bounds.to_vec(),
+            counts: vec![0; bounds.len() + 1],
+            sum: Duration::ZERO,
+        }
+    }
+
+    /// Records a value.
+    pub fn observe(&mut self, value: Duration) {
+        let bucket = self.bounds.partition_point(|bound| *bound < value);
+        self.counts[bucket] += 1;
+        self.sum += value;
+    }
+
+    /// The number of recorded values.
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// The sum of the recorded values.
+    pub fn sum(&self) -> Duration {
+        self.sum
+    }
+
+    /// The mean of the recorded values, or `None` if there are none.
+    pub fn mean(&self) -> Option<Duration> {
+        let count = u32::try_from(self.count()).ok().filter(|n| *n > 0)?;
+        Some(self.sum / count)
+    }
+
+    /// Returns `(upper bound, count)` for each bucket. The last bucket has no
+    /// upper bound.
+    pub fn buckets(&self) -> impl Iterator<Item = (Option<Duration>, u64)> + '_ {
+        self.bounds
+            .iter()
+            .map(|b| Some(*b))
+            .chain([None])
+            .zip(self.counts.iter().copied())
+    }
+}
+
+/// Identifies a request counter: the client method and HTTP status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RequestKey {
+    /// The client method, e.g. `get_session`.
+    pub method: &'static str,
+    /// The HTTP status code, or `None` if no response was received.
+    pub status: Option<u16>,
+}
+
+/// Metrics collected by [`InMemoryMetrics`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MetricsSnapshot {
+    /// Request counts by method and status.
+    pub requests: BTreeMap<RequestKey, u64>,
+    /// Request latencies by method.
+    pub latency: BTreeMap<&'static str, Histogram>,
+    /// Total retries across all requests.
+    pub retries: u64,
+    /// Finished session counts by terminal state, e.g. `COMPLETED`.
+    pub sessions: BTreeMap<&'static str, u64>,
+    /// Durations of sessions that completed successfully.
+    pub time_to_completion: Option<Histogram>,
+}
+
+/// A [`Metrics`] implementation that aggregates in memory.
+///
+/// Share it with the client through an [`Arc`] and read it with
+/// [`snapshot`](Self::snapshot), e.g. to export it periodically.
+#[derive(Debug, Default)]
+pub struct InMemoryMetrics {
+    inner: Mutex<MetricsSnapshot>,
+}
+
+impl InMemoryMetrics {
+    /// Creates an empty collector.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the metrics collected so far.
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        self.lock().clone()
+    }
+
+    /// Returns the metrics collected so far and starts again from zero.
+    pub fn take(&self) -> MetricsSnapshot {
+        std::mem::take(&mut *self.lock())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MetricsSnapshot> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Metrics for InMemoryMetrics {
+    fn on_request(&self, request: &RequestMetrics) {
+        let mut metrics = self.lock();
+        let key = RequestKey {
+            method: request.method,
+            status: request.status.map(|s| s.as_u16()),
+        };
+        *metrics.requests.entry(key).or_default() += 1;
+        metrics
+            .latency
+            .entry(request.method)
+            .or_insert_with(|| Histogram::new(LATENCY_BUCKETS))
+            .observe(request.latency);
+        metrics.retries += u64::from(request.retries);
+    }
+
+    fn on_session_finished(&self, outcome: &SessionOutcome) {
+        let mut metrics = self.lock();
+        *metrics.sessions.entry(outcome.state.as_str()).or_default() += 1;
+        if let (SessionState::Completed, Some(duration)) = (&outcome.state, outcome.duration) {
+            metrics
+                .time_to_completion
+                .get_or_insert_with(|| Histogram::new(SESSION_DURATION_BUCKETS))
+                .observe(duration);
+        }
+    }
+}
+
+impl JulesClient {
+    /// Reports request metrics and session outcomes to `metrics`.
+    ///
+    /// Replaces any metrics set before.
+    pub fn with_metrics(mut self, metrics: impl Metrics) -> Self {
+        self.metrics = Some(Arc::new(metrics));
+        self
+    }
+
+    /// Reports a session's outcome if it is terminal.
+    pub(crate) fn report_session(&self, session: &Session) {
+        if let (Some(metrics), Some(outcome)) = (&self.metrics, SessionOutcome::of(session)) {
+            metrics.on_session_finished(&outcome);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn values_on_a_bound_land_in_that_bucket() {
+        let mut histogram = Histogram::new(&[ms(10), ms(20)]);
+        for value in [0, 10, 11, 20, 21, 1000] {
+            histogram.observe(ms(value));
+        }
+        let buckets: Vec<_> = histogram.buckets().collect();
+        assert_eq!(buckets, [(Some(ms(10)), 2), (Some(ms(20)), 2), (None, 2)]);
+        assert_eq!(histogram.count(), 6);
+        assert_eq!(histogram.sum(), ms(1062));
+        assert_eq!(histogram.mean(), Some(ms(177)));
+    }
+
+    #[test]
+    fn empty_histograms_have_no_mean() {
+        let histogram = Histogram::new(LATENCY_BUCKETS);
+        assert_eq!(histogram.count(), 0);
+        assert_eq!(histogram.mean(), None);
+        assert_eq!(histogram.buckets().count(), LATENCY_BUCKETS.len() + 1);
+    }
+
+    #[test]
+    fn take_resets_the_collector() {
+        let metrics = InMemoryMetrics::new();
+        metrics.on_request(&RequestMetrics {
+            method: "get_session",
+            status: Some(StatusCode::OK),
+            latency: ms(30),
+            retries: 2,
+        });
+        metrics.on_session_finished(&SessionOutcome {
+            session: "sessions/1".to_string(),
+            state: SessionState::Completed,
+            duration: Some(Duration::from_secs(90)),
+        });
+
+        let taken = metrics.take();
+        let key = RequestKey {
+            method: "get_session",
+            status: Some(200),
+        };
+        assert_eq!(taken.requests[&key], 1);
+        assert_eq!(taken.retries, 2);
+        assert_eq!(taken.sessions["COMPLETED"], 1);
+        assert_eq!(taken.time_to_completion.unwrap().count(), 1);
+        assert_eq!(metrics.snapshot(), MetricsSnapshot::default());
+    }
+}
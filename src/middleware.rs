@@ -0,0 +1,287 @@
+//! Middleware for the requests a client sends.
+//!
+//! Every request made by a [`JulesClient`] passes through its middleware
+//! before it is sent. A [`Middleware`] receives the request and a [`Next`]
+//! handle for the rest of the chain, so it can change the request, retry it,
+//! time it, answer it without the network, or inspect the response.
+//!
+//! Middleware added with [`JulesClient::with_middleware`] runs in the order
+//! it was added: the first runs outermost, the last runs closest to the
+//! network.
+//!
+//! With the `tower` feature, a [`tower::Layer`] stack can be used as the
+//! transport with [`JulesClient::with_layer`], so existing tower retry,
+//! timeout, rate limit, and tracing policies apply to Jules requests as well.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use async_trait::async_trait;
+//! use jules_rs::JulesClient;
+//! use jules_rs::middleware::{Middleware, Next};
+//! use jules_rs::transport::{Request, Response};
+//!
+//! /// Tags every request with a team header.
+//! struct TeamHeader(&'static str);
+//!
+//! #[async_trait]
+//! impl Middleware for TeamHeader {
+//!     async fn handle(&self, mut request: Request, next: Next<'_>) -> jules_rs::Result<Response> {
+//!         request.headers_mut().insert("X-Team", self.0.parse().unwrap());
+//!         next.run(request).await
+//!     }
+//! }
+//!
+//! # fn example() -> jules_rs::Result<()> {
+//! let client = JulesClient::new("YOUR_API_KEY")?.with_middleware(TeamHeader("platform"));
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::JulesClient;
+use crate::error::Result;
+use crate::transport::{Request, Response};
+use async_trait::async_trait;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Code that runs around every request a [`JulesClient`] sends.
+///
+/// Implementations call [`Next::run`] to pass the request on, or return a
+/// response of their own to answer it directly.
+#[async_trait]
+pub trait Middleware: Send + Sync + 'static {
+    /// Handles a request, usually by passing it to `next`.
+    async fn handle(&self, request: Request, next: Next<'_>) -> Result<Response>;
+}
+
+/// The rest of a client's middleware chain, ending in the transport.
+#[derive(Clone)]
+pub struct Next<'a> {
+    client: &'a JulesClient,
+    middleware: &'a [Arc<dyn Middleware>],
+    attempts: &'a AtomicU32,
+}
+
+impl<'a> Next<'a> {
+    pub(crate) fn new(
+        client: &'a JulesClient,
+        middleware: &'a [Arc<dyn Middleware>],
+        attempts: &'a AtomicU32,
+    ) -> Self {
+        Self {
+            client,
+            middleware,
+            attempts,
+        }
+    }
+
+    /// The client sending the request.
+    #[cfg(feature = "mock")]
+    pub(crate) fn client(&self) -> &'a JulesClient {
+        self.client
+    }
+
+    /// Passes the request to the next middleware, or sends it if there is
+    /// none left.
+    pub async fn run(self, request: Request) -> Result<Response> {
+        match self.middleware.split_first() {
+            Some((first, rest)) => {
+                let next = Next::new(self.client, rest, self.attempts);
+                first.handle(request, next).await
+            }
+            None => {
+                // Middleware that retries calls `run` again for each attempt.
+                self.attempts.fetch_add(1, Ordering::Relaxed);
+                self.client.transport(request).await
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for Next<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Next")
+            .field("remaining", &self.middleware.len())
+            .finish()
+    }
+}
+
+impl JulesClient {
+    /// Adds middleware that every request passes through.
+    ///
+    /// Middleware runs in the order it was added; see the
+    /// [module documentation](crate::middleware).
+    pub fn with_middleware(mut self, middleware: impl Middleware) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+}
+
+#[cfg(feature = "tower")]
+pub use self::tower_support::HttpService;
+
+#[cfg(feature = "tower")]
+mod tower_support {
+    use crate::client::JulesClient;
+    use crate::error::JulesError;
+    use crate::transport::{HttpTransport, Request, Response};
+    use async_trait::async_trait;
+    use futures_util::future::BoxFuture;
+    use futures_util::lock::Mutex;
+    use std::sync::Arc;
+    use std::task::{Context, Poll};
+    use tower::{Layer, Service};
+
+    /// The [`tower::Service`] that sends requests with the client's
+    /// [`HttpTransport`], wrapped by the layers passed to
+    /// [`JulesClient::with_layer`].
+    #[derive(Clone)]
+    pub struct HttpService {
+        transport: Arc<dyn HttpTransport>,
+    }
+
+    impl std::fmt::Debug for HttpService {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("HttpService").finish_non_exhaustive()
+        }
+    }
+
+    impl Service<Request> for HttpService {
+        type Response = Response;
+        type Error = JulesError;
+        type Future = BoxFuture<'static, Result<Response, JulesError>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: Request) -> Self::Future {
+            let transport = Arc::clone(&self.transport);
+            Box::pin(async move { transport.send(request).await })
+        }
+    }
+
+    pub(crate) type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+    /// A tower stack shared by all requests of a client.
+    struct TowerTransport<S> {
+        service: Mutex<S>,
+    }
+
+    #[async_trait]
+    impl<S> HttpTransport for TowerTransport<S>
+    where
+        S: Service<Request, Response = Response> + Send + 'static,
+        S::Error: Into<BoxError>,
+        S::Future: Send + 'static,
+    {
+        async fn send(&self, request: Request) -> crate::Result<Response> {
+            // Hold the lock only until the service has accepted the request,
+            // so responses are awaited concurrently.
+            let response = {
+                let mut service = self.service.lock().await;
+                futures_util::future::poll_fn(|cx| service.poll_ready(cx))
+                    .await
+                    .map_err(into_error)?;
+                service.call(request)
+            };
+            response.await.map_err(into_error)
+        }
+    }
+
+    impl JulesClient {
+        /// Sends requests through a [`tower`] middleware stack.
+        ///
+        /// `layer` wraps an [`HttpService`] that sends requests with the
+        /// client's current [`HttpTransport`]. The stack is built once and
+        /// shared by every request of this client, so layers that keep
+        /// state across requests, such as concurrency or rate limits, apply
+        /// to all of them. Requests wait their turn for the stack to become
+        /// ready, then their responses are awaited concurrently. The stack
+        /// runs after any [`Middleware`](crate::middleware::Middleware) and
+        /// is not used for GitHub lookups. Errors from the stack are
+        /// returned as [`JulesError::Middleware`], unless they are crate or
+        /// transport errors.
+        ///
+        /// Requires the `tower` feature.
+        ///
+        /// # Example
+        ///
+        /// ```rust,no_run
+        /// use std::time::Duration;
+        /// use jules_rs::JulesClient;
+        /// use tower::ServiceBuilder;
+        ///
+        /// # fn example() -> jules_rs::Result<()> {
+        /// let client = JulesClient::new("YOUR_API_KEY")?.with_layer(
+        ///     ServiceBuilder::new()
+        ///         .concurrency_limit(4)
+        ///         .timeout(Duration::from_secs(30)),
+        /// );
+        /// # Ok(())
+        /// # }
+        /// ```
+        pub fn with_layer<L>(mut self, layer: L) -> Self
+        where
+            L: Layer<HttpService>,
+            L::Service: Service<Request, Response = Response> + Send + 'static,
+            <L::Service as Service<Request>>::Error: Into<BoxError>,
+            <L::Service as Service<Request>>::Future: Send + 'static,
+        {
+            let service = layer.layer(HttpService {
+                transport: Arc::clone(&self.transport),
+            });
+            self.transport = Arc::new(TowerTransport {
+                service: Mutex::new(service),
+            });
+            self
+        }
+    }
+
+    fn into_error(error: impl Into<BoxError>) -> JulesError {
+        let error = error.into();
+        let error = match error.downcast::<JulesError>() {
+            Ok(error) => return *error,
+            Err(error) => error,
+        };
+        #[cfg(feature = "reqwest")]
+        let error = match error.downcast::<reqwest::Error>() {
+            Ok(error) => return JulesError::Http(error),
+            Err(error) => error,
+        };
+        JulesError::Middleware(error)
+    }
+}
+
+#[cfg(all(test, feature = "tower"))]
+mod tests {
+    use crate::JulesClient;
+    use crate::transport::{HttpTransport, Request, Response};
+    use async_trait::async_trait;
+    use std::time::Duration;
+
+    struct NoSessions;
+
+    #[async_trait]
+    impl HttpTransport for NoSessions {
+        async fn send(&self, _request: Request) -> crate::Result<Response> {
+            Ok(Response::new(br#"{"sessions": []}"#.to_vec()))
+        }
+    }
+
+    #[tokio::test]
+    async fn layers_share_state_across_requests() {
+        let client = JulesClient::new("key")
+            .unwrap()
+            .with_transport(NoSessions)
+            .with_layer(tower::limit::RateLimitLayer::new(
+                1,
+                Duration::from_secs(60),
+            ));
+        assert!(client.list_sessions(None, None).await.is_ok());
+        let second =
+            tokio::time::timeout(Duration::from_millis(200), client.list_sessions(None, None));
+        assert!(second.await.is_err(), "the rate limit was not shared");
+    }
+}
@@ -3,6 +3,9 @@
 //! This module contains all the types used to represent Jules API objects,
 //! including sessions, activities, sources, and their related types.
 
+use crate::error::Result;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -42,6 +45,37 @@ pub enum SessionState {
     Completed,
 }
 
+impl SessionState {
+    /// Returns the API's name for the state, e.g. `"AWAITING_PLAN_APPROVAL"`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::StateUnspecified => "STATE_UNSPECIFIED",
+            Self::Queued => "QUEUED",
+            Self::Planning => "PLANNING",
+            Self::AwaitingPlanApproval => "AWAITING_PLAN_APPROVAL",
+            Self::AwaitingUserFeedback => "AWAITING_USER_FEEDBACK",
+            Self::InProgress => "IN_PROGRESS",
+            Self::Paused => "PAUSED",
+            Self::Failed => "FAILED",
+            Self::Completed => "COMPLETED",
+        }
+    }
+
+    /// Returns `true` if the session has finished, successfully or not.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Self::Completed | Self::Failed)
+    }
+
+    /// Returns `true` if the session is waiting for the user to approve a
+    /// plan or to respond.
+    pub fn is_awaiting_user(&self) -> bool {
+        matches!(
+            self,
+            Self::AwaitingPlanApproval | Self::AwaitingUserFeedback
+        )
+    }
+}
+
 /// A coding session with the Jules agent.
 ///
 /// Sessions represent a contiguous amount of work within the same context.
@@ -86,6 +120,34 @@ pub struct Session {
     pub outputs: Option<Vec<SessionOutput>>,
 }
 
+impl Session {
+    /// Converts the session to JSON, including output-only fields.
+    ///
+    /// The [`Serialize`] implementation of `Session` produces a request body
+    /// and therefore omits output-only fields such as `state`, `createTime`,
+    /// and `outputs`. Use this method when the full session should be
+    /// written out, e.g. for storage or display.
+    pub fn to_json_value(&self) -> Result<serde_json::Value> {
+        let mut value = serde_json::to_value(self)?;
+        if let Some(obj) = value.as_object_mut() {
+            obj.insert("createTime".into(), serde_json::to_value(self.create_time)?);
+            obj.insert("updateTime".into(), serde_json::to_value(self.update_time)?);
+            obj.insert("state".into(), serde_json::to_value(&self.state)?);
+            obj.insert("url".into(), serde_json::to_value(&self.url)?);
+            obj.insert("outputs".into(), serde_json::to_value(&self.outputs)?);
+        }
+        Ok(value)
+    }
+
+    /// Returns the pull requests the session has created, oldest first.
+    pub fn pull_requests(&self) -> impl DoubleEndedIterator<Item = &PullRequest> {
+        self.outputs
+            .iter()
+            .flatten()
+            .filter_map(|o| o.pull_request.as_ref())
+    }
+}
+
 /// Context for using a source in a session.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -125,6 +187,64 @@ pub struct PullRequest {
     pub description: String,
 }
 
+impl PullRequest {
+    /// Returns the owner of the repository, parsed from the URL.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use jules_rs::PullRequest;
+    ///
+    /// let pr = PullRequest {
+    ///     url: "https://github.com/acme/widgets/pull/42".to_string(),
+    ///     title: "Fix login".to_string(),
+    ///     description: String::new(),
+    /// };
+    /// assert_eq!(pr.owner(), Some("acme"));
+    /// assert_eq!(pr.repo(), Some("widgets"));
+    /// assert_eq!(pr.number(), Some(42));
+    /// ```
+    pub fn owner(&self) -> Option<&str> {
+        self.parse_url().map(|(owner, _, _)| owner)
+    }
+
+    /// Returns the name of the repository, parsed from the URL.
+    pub fn repo(&self) -> Option<&str> {
+        self.parse_url().map(|(_, repo, _)| repo)
+    }
+
+    /// Returns the pull request number, parsed from the URL.
+    pub fn number(&self) -> Option<u64> {
+        self.parse_url().map(|(_, _, number)| number)
+    }
+
+    /// Returns `true` if the pull request belongs to the repository of the
+    /// given source.
+    pub fn is_from(&self, source: &Source) -> bool {
+        match (self.parse_url(), &source.github_repo) {
+            (Some((owner, repo, _)), Some(gh)) => {
+                gh.owner.eq_ignore_ascii_case(owner) && gh.repo.eq_ignore_ascii_case(repo)
+            }
+            _ => false,
+        }
+    }
+
+    /// Splits `https://github.com/{owner}/{repo}/pull/{number}` into its parts.
+    fn parse_url(&self) -> Option<(&str, &str, u64)> {
+        let path = self.url.trim().split_once("://")?.1;
+        let path = path.strip_prefix("www.").unwrap_or(path);
+        let path = path.strip_prefix("github.com/")?;
+        let mut parts = path.split('/');
+        let owner = parts.next().filter(|s| !s.is_empty())?;
+        let repo = parts.next().filter(|s| !s.is_empty())?;
+        if parts.next()? != "pull" {
+            return None;
+        }
+        let number = parts.next()?.split(['?', '#']).next()?.parse().ok()?;
+        Some((owner, repo, number))
+    }
+}
+
 /// An activity within a session.
 ///
 /// Activities represent individual units of work or events that occur
@@ -160,6 +280,16 @@ pub struct Activity {
     pub artifacts: Option<Vec<Artifact>>,
 }
 
+impl Activity {
+    /// Returns the git patches attached to this activity's artifacts.
+    pub fn git_patches(&self) -> impl Iterator<Item = &GitPatch> {
+        self.artifacts
+            .iter()
+            .flatten()
+            .filter_map(|a| a.change_set.as_ref()?.git_patch.as_ref())
+    }
+}
+
 /// Agent message activity.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -270,6 +400,98 @@ pub struct GitPatch {
     pub suggested_commit_message: Option<String>,
 }
 
+impl GitPatch {
+    /// Summarises the patch per file.
+    ///
+    /// Files are listed in the order they appear in the patch.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use jules_rs::GitPatch;
+    ///
+    /// let patch = GitPatch {
+    ///     unidiff_patch: "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1,2 @@\n-old\n+new\n+more\n"
+    ///         .to_string(),
+    ///     base_commit_id: "abc123".to_string(),
+    ///     suggested_commit_message: None,
+    /// };
+    /// let stats = patch.diff_stats();
+    /// assert_eq!(stats[0].path, "src/lib.rs");
+    /// assert_eq!((stats[0].insertions, stats[0].deletions), (2, 1));
+    /// ```
+    pub fn diff_stats(&self) -> Vec<DiffStat> {
+        let mut stats: Vec<DiffStat> = Vec::new();
+        let mut old_path: Option<&str> = None;
+        // Lines left in the current hunk, so that removed lines starting with
+        // `--` are not mistaken for file headers.
+        let (mut old_left, mut new_left) = (0usize, 0usize);
+        for line in self.unidiff_patch.lines() {
+            if old_left > 0 || new_left > 0 {
+                let Some(stat) = stats.last_mut() else {
+                    break;
+                };
+                match line.as_bytes().first() {
+                    Some(b'+') => {
+                        stat.insertions += 1;
+                        new_left = new_left.saturating_sub(1);
+                    }
+                    Some(b'-') => {
+                        stat.deletions += 1;
+                        old_left = old_left.saturating_sub(1);
+                    }
+                    Some(b'\\') => {}
+                    _ => {
+                        old_left = old_left.saturating_sub(1);
+                        new_left = new_left.saturating_sub(1);
+                    }
+                }
+            } else if let Some(path) = line.strip_prefix("--- ") {
+                old_path = Some(path);
+            } else if let Some(path) = line.strip_prefix("+++ ") {
+                // Deleted files have `+++ /dev/null`; name them by the old path.
+                let path = match (path, old_path) {
+                    ("/dev/null", Some(old)) => old,
+                    _ => path,
+                };
+                let path = path.split('\t').next().unwrap_or(path);
+                let path = path
+                    .strip_prefix("a/")
+                    .or_else(|| path.strip_prefix("b/"))
+                    .unwrap_or(path);
+                stats.push(DiffStat {
+                    path: path.to_string(),
+                    insertions: 0,
+                    deletions: 0,
+                });
+            } else if let Some(header) = line.strip_prefix("@@ ") {
+                // `@@ -start[,count] +start[,count] @@`; a missing count is 1.
+                let count = |range: Option<&str>| {
+                    range
+                        .and_then(|r| r.split(',').nth(1).or(Some("1")))
+                        .and_then(|c| c.parse().ok())
+                        .unwrap_or(0)
+                };
+                let mut ranges = header.split_whitespace();
+                old_left = count(ranges.next());
+                new_left = count(ranges.next());
+            }
+        }
+        stats
+    }
+}
+
+/// Per-file line counts of a [`GitPatch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffStat {
+    /// Path of the changed file, without the `a/` or `b/` prefix.
+    pub path: String,
+    /// Number of added lines.
+    pub insertions: usize,
+    /// Number of removed lines.
+    pub deletions: usize,
+}
+
 /// A media artifact.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -280,6 +502,65 @@ pub struct Media {
     pub mime_type: String,
 }
 
+impl Media {
+    /// Decodes the base64-encoded [`data`](Self::data) into raw bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JulesError::Decode`](crate::JulesError::Decode) if the data
+    /// is not valid base64.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use jules_rs::Media;
+    ///
+    /// let media = Media {
+    ///     data: "aGVsbG8=".to_string(),
+    ///     mime_type: "image/png".to_string(),
+    /// };
+    /// assert_eq!(media.bytes().unwrap(), b"hello");
+    /// ```
+    pub fn bytes(&self) -> Result<Vec<u8>> {
+        Ok(STANDARD.decode(self.data.trim())?)
+    }
+
+    /// Returns the conventional file extension for the media's MIME type.
+    ///
+    /// Returns `None` if the MIME type is not recognised.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use jules_rs::Media;
+    ///
+    /// let media = Media {
+    ///     data: String::new(),
+    ///     mime_type: "image/jpeg".to_string(),
+    /// };
+    /// assert_eq!(media.file_extension(), Some("jpg"));
+    /// ```
+    pub fn file_extension(&self) -> Option<&'static str> {
+        let essence = self.mime_type.split(';').next().unwrap_or_default().trim();
+        let ext = match essence.to_ascii_lowercase().as_str() {
+            "image/png" => "png",
+            "image/jpeg" | "image/jpg" => "jpg",
+            "image/gif" => "gif",
+            "image/webp" => "webp",
+            "image/svg+xml" => "svg",
+            "image/bmp" => "bmp",
+            "video/mp4" => "mp4",
+            "video/webm" => "webm",
+            "video/quicktime" => "mov",
+            "text/plain" => "txt",
+            "application/json" => "json",
+            "application/pdf" => "pdf",
+            _ => return None,
+        };
+        Some(ext)
+    }
+}
+
 /// Output from a bash command.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -320,6 +601,14 @@ pub struct GitHubRepo {
     pub branches: Vec<GitHubBranch>,
 }
 
+impl GitHubRepo {
+    /// Returns `true` if the repository has a branch with the given name.
+    pub fn has_branch(&self, name: &str) -> bool {
+        self.default_branch.display_name == name
+            || self.branches.iter().any(|b| b.display_name == name)
+    }
+}
+
 /// A GitHub branch.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -0,0 +1,735 @@
+//! Running many sessions under a concurrency limit.
+//!
+//! An [`Orchestrator`] accepts session requests, starts at most
+//! [`max_concurrent`](Orchestrator::max_concurrent) of them at a time, and
+//! polls each one through its [`SessionState`]s until it completes or fails.
+//! Failed sessions and failed create calls are retried according to a
+//! [`RetryPolicy`]. A [`snapshot`](Orchestrator::snapshot) of every request
+//! can be taken at any time, including while [`run`](Orchestrator::run) is in
+//! progress, to drive a progress display. Between polls it waits with the
+//! client's [`Sleep`](crate::transport::Sleep); without the `tokio-runtime`
+//! feature, set one with [`JulesClient::with_sleep`].
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use jules_rs::{JulesClient, Session};
+//! use jules_rs::orchestrator::{Orchestrator, RetryPolicy};
+//!
+//! # async fn example(sessions: Vec<Session>) -> Result<(), Box<dyn std::error::Error>> {
+//! let client = JulesClient::new("TOKEN")?;
+//! let orchestrator = Orchestrator::new(&client)
+//!     .max_concurrent(10)
+//!     .retry_policy(RetryPolicy::default().resubmits(1));
+//! for session in sessions {
+//!     orchestrator.submit(session);
+//! }
+//!
+//! let progress = async {
+//!     loop {
+//!         let snapshot = orchestrator.snapshot();
+//!         println!(
+//!             "{} running, {} completed, {} failed",
+//!             snapshot.running(),
+//!             snapshot.completed(),
+//!             snapshot.failed()
+//!         );
+//!         tokio::time::sleep(Duration::from_secs(60)).await;
+//!     }
+//! };
+//! let snapshot = tokio::select! {
+//!     snapshot = orchestrator.run() => snapshot,
+//!     _ = progress => unreachable!(),
+//! };
+//! for task in snapshot.tasks {
+//!     println!("{:?}: {:?} {:?}", task.id, task.status, task.session);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::JulesClient;
+use crate::error::JulesError;
+use crate::models::{Session, SessionState};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Identifies a request submitted to an [`Orchestrator`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(transparent)]
+pub struct TaskId(pub usize);
+
+/// Where a request is in its lifecycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    /// Waiting for a free slot, or for its retry backoff to pass.
+    Queued,
+    /// A session exists and has not finished.
+    Running,
+    /// The session completed.
+    Completed,
+    /// The session failed and no resubmits are left.
+    Failed,
+    /// The session could not be created and no attempts are left.
+    Error,
+    /// The session waited for the user or stayed paused for longer than the
+    /// [stall timeout](Orchestrator::stall_timeout), and was given up on.
+    Stalled,
+}
+
+impl TaskStatus {
+    /// Whether the request will not change any more.
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            Self::Completed | Self::Failed | Self::Error | Self::Stalled
+        )
+    }
+}
+
+/// How an [`Orchestrator`] handles failures.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    create_attempts: u32,
+    resubmits: u32,
+    backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            create_attempts: 3,
+            resubmits: 0,
+            backoff: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    pub fn none() -> Self {
+        Self {
+            create_attempts: 1,
+            resubmits: 0,
+            backoff: Duration::ZERO,
+        }
+    }
+
+    /// Sets how many times creating a session is attempted before the
+    /// request is marked [`TaskStatus::Error`] (default 3).
+    pub fn create_attempts(mut self, attempts: u32) -> Self {
+        self.create_attempts = attempts.max(1);
+        self
+    }
+
+    /// Sets how many times a request whose session failed is resubmitted as
+    /// a new session (default 0).
+    pub fn resubmits(mut self, resubmits: u32) -> Self {
+        self.resubmits = resubmits;
+        self
+    }
+
+    /// Sets how long a request waits before it is retried or resubmitted
+    /// (default 10 seconds).
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+}
+
+/// The progress of one request.
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskSnapshot {
+    /// The request's id.
+    pub id: TaskId,
+    /// The session's title, or its prompt if it has no title.
+    pub label: String,
+    /// Where the request is in its lifecycle.
+    pub status: TaskStatus,
+    /// The last observed state of the current session.
+    pub state: Option<SessionState>,
+    /// Resource name of the current session.
+    pub session: Option<String>,
+    /// URL of the current session in the Jules web app.
+    pub url: Option<String>,
+    /// Sessions created for earlier attempts that failed, oldest first.
+    pub previous_sessions: Vec<String>,
+    /// How many sessions have been created for the request.
+    pub submissions: u32,
+    /// The most recent error, from creating or polling the session.
+    pub error: Option<String>,
+    /// When the state or status last changed.
+    pub updated_at: DateTime<Utc>,
+}
+
+/// The progress of every request, in submission order.
+#[derive(Debug, Clone, Serialize)]
+pub struct OrchestratorSnapshot {
+    /// One entry per submitted request.
+    pub tasks: Vec<TaskSnapshot>,
+}
+
+impl OrchestratorSnapshot {
+    /// Number of requests with the given status.
+    pub fn count(&self, status: TaskStatus) -> usize {
+        self.tasks.iter().filter(|t| t.status == status).count()
+    }
+
+    /// Number of requests waiting to start.
+    pub fn queued(&self) -> usize {
+        self.count(TaskStatus::Queued)
+    }
+
+    /// Number of requests with an unfinished session.
+    pub fn running(&self) -> usize {
+        self.count(TaskStatus::Running)
+    }
+
+    /// Number of requests whose session completed.
+    pub fn completed(&self) -> usize {
+        self.count(TaskStatus::Completed)
+    }
+
+    /// Number of requests that failed or could not be created.
+    pub fn failed(&self) -> usize {
+        self.count(TaskStatus::Failed) + self.count(TaskStatus::Error)
+    }
+
+    /// Number of requests given up on after their session stalled.
+    pub fn stalled(&self) -> usize {
+        self.count(TaskStatus::Stalled)
+    }
+
+    /// Whether every request has finished.
+    pub fn is_finished(&self) -> bool {
+        self.tasks.iter().all(|t| t.status.is_finished())
+    }
+}
+
+struct Task {
+    request: Session,
+    snapshot: TaskSnapshot,
+    create_failures: u32,
+    not_before: Option<Instant>,
+    /// Whether the plan the session is awaiting approval for has been
+    /// approved, so it is approved once rather than on every poll.
+    approved_plan: bool,
+    /// When the session entered its current state.
+    state_since: Instant,
+}
+
+/// Starts and tracks many sessions under a concurrency limit.
+///
+/// See the [module documentation](self) for an example.
+pub struct Orchestrator<'a> {
+    client: &'a JulesClient,
+    max_concurrent: usize,
+    poll_interval: Duration,
+    retry: RetryPolicy,
+    approve_plans: bool,
+    stall_timeout: Option<Duration>,
+    tasks: Mutex<Vec<Task>>,
+}
+
+impl<'a> Orchestrator<'a> {
+    /// Creates an orchestrator with no requests.
+    pub fn new(client: &'a JulesClient) -> Self {
+        Self {
+            client,
+            max_concurrent: 5,
+            poll_interval: Duration::from_secs(30),
+            retry: RetryPolicy::default(),
+            approve_plans: false,
+            stall_timeout: None,
+            tasks: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Sets how many sessions may be unfinished at the same time (default 5).
+    pub fn max_concurrent(mut self, max: usize) -> Self {
+        self.max_concurrent = max.max(1);
+        self
+    }
+
+    /// Sets how often running sessions are polled (default 30 seconds).
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Sets how failures are retried.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Approves plans of sessions awaiting plan approval, so they do not
+    /// hold a slot until someone approves them by hand.
+    pub fn auto_approve_plans(mut self, approve: bool) -> Self {
+        self.approve_plans = approve;
+        self
+    }
+
+    /// Gives up on sessions that stay awaiting plan approval, awaiting user
+    /// feedback, or paused for longer than `timeout`, marking them
+    /// [`TaskStatus::Stalled`] to free their slot.
+    ///
+    /// By default such sessions hold their slot until they move on, so
+    /// [`run`](Self::run) does not return while one is waiting for input.
+    pub fn stall_timeout(mut self, timeout: Duration) -> Self {
+        self.stall_timeout = Some(timeout);
+        self
+    }
+
+    /// Queues a session to be created.
+    ///
+    /// Requests may also be submitted while [`run`](Self::run) is in
+    /// progress.
+    pub fn submit(&self, request: Session) -> TaskId {
+        let mut tasks = self.lock();
+        let id = TaskId(tasks.len());
+        let label = request.title.clone().unwrap_or_else(|| {
+            request
+                .prompt
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string()
+        });
+        tasks.push(Task {
+            request,
+            snapshot: TaskSnapshot {
+                id,
+                label,
+                status: TaskStatus::Queued,
+                state: None,
+                session: None,
+                url: None,
+                previous_sessions: Vec::new(),
+                submissions: 0,
+                error: None,
+                updated_at: Utc::now(),
+            },
+            create_failures: 0,
+            not_before: None,
+            approved_plan: false,
+            state_since: Instant::now(),
+        });
+        id
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Task>> {
+        self.tasks.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Returns the current progress of every request.
+    pub fn snapshot(&self) -> OrchestratorSnapshot {
+        let tasks = self.lock();
+        OrchestratorSnapshot {
+            tasks: tasks.iter().map(|t| t.snapshot.clone()).collect(),
+        }
+    }
+
+    /// Starts queued requests and polls running sessions until every
+    /// request has finished, then returns the final snapshot.
+    ///
+    /// A session that waits for input keeps its slot, and the run does not
+    /// return, until someone responds or the
+    /// [`stall_timeout`](Self::stall_timeout) passes. With
+    /// [`auto_approve_plans`](Self::auto_approve_plans), plan approvals do
+    /// not count as input.
+    ///
+    /// Errors from individual requests never stop the run; they are
+    /// recorded in [`TaskSnapshot::error`]. A session that cannot be polled
+    /// is polled again on the next round.
+    ///
+    /// If the run has to wait between polls and the client has no
+    /// [`Sleep`](crate::transport::Sleep), it stops and marks every
+    /// unfinished request as [`TaskStatus::Error`] with
+    /// [`JulesError::NoSleep`] as the error.
+    pub async fn run(&self) -> OrchestratorSnapshot {
+        loop {
+            self.start_queued().await;
+            self.poll_running().await;
+            if self.snapshot().is_finished() {
+                return self.snapshot();
+            }
+            match self.client.sleeper() {
+                Ok(sleep) => sleep.sleep(self.poll_interval).await,
+                Err(e) => {
+                    self.abandon(&e);
+                    return self.snapshot();
+                }
+            }
+        }
+    }
+
+    /// Marks every unfinished request as [`TaskStatus::Error`] with `error`.
+    fn abandon(&self, error: &JulesError) {
+        for task in self.lock().iter_mut() {
+            if !task.snapshot.status.is_finished() {
+                task.snapshot.status = TaskStatus::Error;
+                task.snapshot.error = Some(error.to_string());
+                task.snapshot.updated_at = Utc::now();
+            }
+        }
+    }
+
+    /// Creates sessions for queued requests while slots are free.
+    async fn start_queued(&self) {
+        loop {
+            let next = {
+                let tasks = self.lock();
+                let running = tasks
+                    .iter()
+                    .filter(|t| t.snapshot.status == TaskStatus::Running)
+                    .count();
+                if running >= self.max_concurrent {
+                    return;
+                }
+                let now = Instant::now();
+                tasks
+                    .iter()
+                    .find(|t| {
+                        t.snapshot.status == TaskStatus::Queued
+                            && t.not_before.is_none_or(|at| at <= now)
+                    })
+                    .map(|t| (t.snapshot.id, t.request.clone()))
+            };
+            let Some((id, request)) = next else {
+                return;
+            };
+
+            let result = self.client.create_session(&request).await;
+            let mut tasks = self.lock();
+            let task = &mut tasks[id.0];
+            task.snapshot.updated_at = Utc::now();
+            match result {
+                Ok(created) => {
+                    task.create_failures = 0;
+                    task.snapshot.status = TaskStatus::Running;
+                    task.snapshot.submissions += 1;
+                    task.snapshot.state = None;
+                    task.snapshot.session = created.name.clone();
+                    task.snapshot.url = created.url.clone();
+                    task.snapshot.error = None;
+                    // A session can already have finished when it is
+                    // returned; plans are approved on the next poll.
+                    if self.observe(task, &created) {
+                        task.approved_plan = false;
+                    }
+                }
+                Err(e) => {
+                    task.create_failures += 1;
+                    task.snapshot.error = Some(e.to_string());
+                    if task.create_failures >= self.retry.create_attempts {
+                        task.snapshot.status = TaskStatus::Error;
+                    } else {
+                        task.not_before = Some(Instant::now() + self.retry.backoff);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Refreshes the state of every running session.
+    async fn poll_running(&self) {
+        let running: Vec<(TaskId, String)> = {
+            let tasks = self.lock();
+            tasks
+                .iter()
+                .filter(|t| t.snapshot.status == TaskStatus::Running)
+                .filter_map(|t| Some((t.snapshot.id, t.snapshot.session.clone()?)))
+                .collect()
+        };
+
+        for (id, name) in running {
+            let result = self.client.get_session(&name).await;
+            let approve = {
+                let mut tasks = self.lock();
+                let task = &mut tasks[id.0];
+                match result {
+                    Ok(session) => {
+                        task.snapshot.error = None;
+                        let approve = self.observe(task, &session);
+                        self.check_stalled(task);
+                        approve && task.snapshot.status == TaskStatus::Running
+                    }
+                    Err(e) => {
+                        task.snapshot.error = Some(e.to_string());
+                        false
+                    }
+                }
+            };
+            if approve {
+                if let Err(e) = self.client.approve_plan(&name).await {
+                    // Approve again on the next poll.
+                    let task = &mut self.lock()[id.0];
+                    task.approved_plan = false;
+                    task.snapshot.error = Some(e.to_string());
+                }
+            }
+        }
+    }
+
+    /// Gives up on a running session that has waited in a state needing the
+    /// user for longer than the stall timeout.
+    fn check_stalled(&self, task: &mut Task) {
+        let (Some(timeout), Some(state)) = (self.stall_timeout, &task.snapshot.state) else {
+            return;
+        };
+        let waiting = state.is_awaiting_user() || *state == SessionState::Paused;
+        if task.snapshot.status == TaskStatus::Running
+            && waiting
+            && task.state_since.elapsed() >= timeout
+        {
+            task.snapshot.status = TaskStatus::Stalled;
+            task.snapshot.error = Some(format!(
+                "session stayed {} for longer than {timeout:?}",
+                state.as_str()
+            ));
+            task.snapshot.updated_at = Utc::now();
+        }
+    }
+
+    /// Records a session's state, returning whether its plan should be
+    /// approved. A plan is approved once per visit to
+    /// [`SessionState::AwaitingPlanApproval`].
+    fn observe(&self, task: &mut Task, session: &Session) -> bool {
+        let Some(state) = session.state.clone() else {
+            return false;
+        };
+        if state != SessionState::AwaitingPlanApproval {
+            task.approved_plan = false;
+        }
+        let approve = self.approve_plans
+            && state == SessionState::AwaitingPlanApproval
+            && !std::mem::replace(&mut task.approved_plan, true);
+        if task.snapshot.state.as_ref() == Some(&state) {
+            return approve;
+        }
+        task.snapshot.updated_at = Utc::now();
+        task.state_since = Instant::now();
+        self.client.report_session(session);
+        match state {
+            SessionState::Completed => task.snapshot.status = TaskStatus::Completed,
+            SessionState::Failed => {
+                if task.snapshot.submissions <= self.retry.resubmits {
+                    task.snapshot.status = TaskStatus::Queued;
+                    task.snapshot
+                        .previous_sessions
+                        .extend(task.snapshot.session.take());
+                    task.snapshot.url = None;
+                    task.not_before = Some(Instant::now() + self.retry.backoff);
+                    task.snapshot.state = None;
+                    task.approved_plan = false;
+                    return false;
+                }
+                task.snapshot.status = TaskStatus::Failed;
+            }
+            _ => {}
+        }
+        task.snapshot.state = Some(state);
+        approve
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{GitHubRepoContext, SourceContext};
+    use crate::transport::{HttpTransport, Request, Response, ThreadSleep};
+    use async_trait::async_trait;
+    use serde_json::{Value, json};
+    use std::collections::VecDeque;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    /// Serves one session that goes through `states`, staying in the last
+    /// one, and fails the first `failing_approvals` plan approvals.
+    #[derive(Default)]
+    struct Fake {
+        states: Mutex<VecDeque<&'static str>>,
+        approvals: AtomicUsize,
+        failing_approvals: AtomicUsize,
+    }
+
+    impl Fake {
+        fn new(states: &[&'static str]) -> Arc<Self> {
+            Arc::new(Self {
+                states: Mutex::new(states.iter().copied().collect()),
+                ..Self::default()
+            })
+        }
+
+        fn session(&self, state: &str) -> Value {
+            let mut session = serde_json::to_value(request("task")).unwrap();
+            session["name"] = json!("sessions/1");
+            session["state"] = json!(state);
+            session
+        }
+    }
+
+    #[async_trait]
+    impl HttpTransport for Fake {
+        async fn send(&self, request: Request) -> crate::Result<Response> {
+            let path = request.uri().path().to_string();
+            let body = if path.ends_with(":approvePlan") {
+                self.approvals.fetch_add(1, Ordering::SeqCst);
+                let failing = &self.failing_approvals;
+                if failing.load(Ordering::SeqCst) > 0 {
+                    failing.fetch_sub(1, Ordering::SeqCst);
+                    let mut response = Response::new(b"{}".to_vec());
+                    *response.status_mut() = http::StatusCode::SERVICE_UNAVAILABLE;
+                    return Ok(response);
+                }
+                json!({})
+            } else if path.ends_with("/sessions") {
+                self.session("QUEUED")
+            } else {
+                let mut states = self.states.lock().unwrap();
+                let state = match states.len() {
+                    1 => states[0],
+                    _ => states.pop_front().unwrap(),
+                };
+                self.session(state)
+            };
+            Ok(Response::new(serde_json::to_vec(&body)?))
+        }
+    }
+
+    async fn run(orchestrator: &Orchestrator<'_>) -> OrchestratorSnapshot {
+        let run = tokio::time::timeout(Duration::from_secs(10), orchestrator.run());
+        run.await.expect("the orchestrator should finish")
+    }
+
+    fn client(fake: &Arc<Fake>) -> JulesClient {
+        JulesClient::new("key")
+            .unwrap()
+            .with_transport(Arc::clone(fake))
+            .with_sleep(ThreadSleep)
+    }
+
+    fn request(prompt: &str) -> Session {
+        Session {
+            name: None,
+            id: None,
+            prompt: prompt.to_string(),
+            source_context: SourceContext {
+                source: "sources/github/acme/widgets".to_string(),
+                github_repo_context: Some(GitHubRepoContext {
+                    starting_branch: "main".to_string(),
+                }),
+            },
+            title: None,
+            require_plan_approval: None,
+            automation_mode: None,
+            create_time: None,
+            update_time: None,
+            state: None,
+            url: None,
+            outputs: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn approves_each_plan_once() {
+        let fake = Fake::new(&[
+            "PLANNING",
+            "AWAITING_PLAN_APPROVAL",
+            "AWAITING_PLAN_APPROVAL",
+            "AWAITING_PLAN_APPROVAL",
+            "IN_PROGRESS",
+            "COMPLETED",
+        ]);
+        let client = client(&fake);
+        let orchestrator = Orchestrator::new(&client)
+            .poll_interval(Duration::from_millis(1))
+            .auto_approve_plans(true);
+        orchestrator.submit(request("task"));
+
+        assert_eq!(run(&orchestrator).await.completed(), 1);
+        assert_eq!(fake.approvals.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn retries_failed_plan_approvals() {
+        let fake = Fake::new(&[
+            "AWAITING_PLAN_APPROVAL",
+            "AWAITING_PLAN_APPROVAL",
+            "AWAITING_PLAN_APPROVAL",
+            "AWAITING_PLAN_APPROVAL",
+            "COMPLETED",
+        ]);
+        fake.failing_approvals.store(1, Ordering::SeqCst);
+        let client = client(&fake);
+        let orchestrator = Orchestrator::new(&client)
+            .poll_interval(Duration::from_millis(1))
+            .auto_approve_plans(true);
+        orchestrator.submit(request("task"));
+
+        assert_eq!(run(&orchestrator).await.completed(), 1);
+        assert_eq!(fake.approvals.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn gives_up_on_stalled_sessions() {
+        let fake = Fake::new(&["IN_PROGRESS", "AWAITING_USER_FEEDBACK"]);
+        let client = client(&fake);
+        let orchestrator = Orchestrator::new(&client)
+            .poll_interval(Duration::from_millis(5))
+            .stall_timeout(Duration::from_millis(50));
+        let id = orchestrator.submit(request("task"));
+
+        let snapshot = run(&orchestrator).await;
+        let task = &snapshot.tasks[id.0];
+        assert_eq!(task.status, TaskStatus::Stalled);
+        assert_eq!(task.state, Some(SessionState::AwaitingUserFeedback));
+        assert!(
+            task.error
+                .as_deref()
+                .unwrap()
+                .contains("AWAITING_USER_FEEDBACK")
+        );
+        assert_eq!(snapshot.stalled(), 1);
+    }
+
+    #[tokio::test]
+    async fn keeps_sessions_that_move_on_within_the_stall_timeout() {
+        let fake = Fake::new(&["PAUSED", "PAUSED", "IN_PROGRESS", "COMPLETED"]);
+        let client = client(&fake);
+        let orchestrator = Orchestrator::new(&client)
+            .poll_interval(Duration::from_millis(1))
+            .stall_timeout(Duration::from_secs(5));
+        orchestrator.submit(request("task"));
+
+        assert_eq!(run(&orchestrator).await.completed(), 1);
+    }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn finishes_when_sessions_are_created_terminal() {
+        use crate::testing::{Event, MockServer, Script};
+
+        let server = MockServer::start();
+        server.add_github_source("acme", "widgets", &["main"]);
+        server.on(Event::Create, Script::new().complete());
+        let client = server.client();
+        let orchestrator = Orchestrator::new(&client).poll_interval(Duration::from_millis(10));
+        orchestrator.submit(request("a"));
+        orchestrator.submit(request("b"));
+
+        let snapshot = run(&orchestrator).await;
+        assert_eq!(snapshot.completed(), 2);
+        assert!(
+            snapshot
+                .tasks
+                .iter()
+                .all(|t| t.state == Some(SessionState::Completed))
+        );
+    }
+}
@@ -0,0 +1,493 @@
+//! Client-side filtering of sessions.
+//!
+//! The sessions list endpoint only supports pagination, so any filtering has
+//! to happen on the client. [`SessionQuery`] describes the sessions you are
+//! interested in and runs over [`JulesClient::stream_sessions`], stopping
+//! once its [`limit`](SessionQuery::limit) is reached.
+//!
+//! # Example
+//!
+//! Find all failed sessions on a repository from the last week:
+//!
+//! ```rust,no_run
+//! use chrono::{Duration, Utc};
+//! use jules_rs::{JulesClient, SessionQuery, SessionState};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = JulesClient::new("TOKEN")?;
+//! let failed = SessionQuery::new()
+//!     .state(SessionState::Failed)
+//!     .repo("acme", "widgets")
+//!     .created_after(Utc::now() - Duration::days(7))
+//!     .run(&client)
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::JulesClient;
+use crate::error::Result;
+use crate::models::{Session, SessionState};
+use chrono::{DateTime, Utc};
+use futures_util::{StreamExt, stream::Stream};
+use std::collections::HashSet;
+use std::pin::Pin;
+
+/// A filter over sessions, evaluated on the client.
+///
+/// All configured conditions must hold for a session to match. Conditions of
+/// the same kind are alternatives: `.state(Failed).state(Paused)` matches
+/// sessions in either state.
+///
+/// Every session is fetched and checked. Call
+/// [`assume_newest_first`](Self::assume_newest_first) to stop paging early
+/// when filtering on `create_time`.
+#[derive(Debug, Clone, Default)]
+pub struct SessionQuery {
+    states: Vec<SessionState>,
+    sources: Vec<String>,
+    repos: Vec<(String, String)>,
+    created_after: Option<DateTime<Utc>>,
+    created_before: Option<DateTime<Utc>>,
+    updated_after: Option<DateTime<Utc>>,
+    updated_before: Option<DateTime<Utc>>,
+    text: Option<String>,
+    has_pull_request: Option<bool>,
+    limit: Option<usize>,
+    newest_first: bool,
+}
+
+impl SessionQuery {
+    /// Creates a query that matches every session.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Matches sessions in the given state.
+    pub fn state(mut self, state: SessionState) -> Self {
+        self.states.push(state);
+        self
+    }
+
+    /// Matches sessions in any of the given states.
+    pub fn states(mut self, states: impl IntoIterator<Item = SessionState>) -> Self {
+        self.states.extend(states);
+        self
+    }
+
+    /// Matches sessions on the given source (e.g. `sources/github/acme/widgets`).
+    pub fn source(mut self, source: impl Into<String>) -> Self {
+        self.sources.push(source.into());
+        self
+    }
+
+    /// Matches sessions on the given GitHub repository.
+    ///
+    /// The repository is resolved to its source through
+    /// [`list_sources`](JulesClient::list_sources) when the query runs.
+    /// Owner and repository names are compared case-insensitively.
+    pub fn repo(mut self, owner: impl Into<String>, repo: impl Into<String>) -> Self {
+        self.repos.push((owner.into(), repo.into()));
+        self
+    }
+
+    /// Matches sessions created at or after `time`.
+    pub fn created_after(mut self, time: DateTime<Utc>) -> Self {
+        self.created_after = Some(time);
+        self
+    }
+
+    /// Matches sessions created before `time`.
+    pub fn created_before(mut self, time: DateTime<Utc>) -> Self {
+        self.created_before = Some(time);
+        self
+    }
+
+    /// Matches sessions updated at or after `time`.
+    pub fn updated_after(mut self, time: DateTime<Utc>) -> Self {
+        self.updated_after = Some(time);
+        self
+    }
+
+    /// Matches sessions updated before `time`.
+    pub fn updated_before(mut self, time: DateTime<Utc>) -> Self {
+        self.updated_before = Some(time);
+        self
+    }
+
+    /// Matches sessions whose title or prompt contains `text`, ignoring case.
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.text = Some(text.into().to_lowercase());
+        self
+    }
+
+    /// Matches sessions that have (or, with `false`, have not) produced a
+    /// pull request.
+    pub fn has_pull_request(mut self, has_pull_request: bool) -> Self {
+        self.has_pull_request = Some(has_pull_request);
+        self
+    }
+
+    /// Stops after `limit` matching sessions.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Stops paging at the first session created before the
+    /// [`created_after`](Self::created_after) bound.
+    ///
+    /// This assumes the API lists sessions newest first, which the API
+    /// reference does not promise. If a session arrives out of descending
+    /// `create_time` order, the query falls back to scanning all sessions,
+    /// but an ordering that merely looks sorted up to the bound can still
+    /// cause matching sessions further on to be missed.
+    pub fn assume_newest_first(mut self) -> Self {
+        self.newest_first = true;
+        self
+    }
+
+    /// Runs the query and collects the matching sessions.
+    pub async fn run(self, client: &JulesClient) -> Result<Vec<Session>> {
+        self.stream(client)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect()
+    }
+
+    /// Returns an async stream over the matching sessions.
+    ///
+    /// Pages are fetched lazily as the stream is polled.
+    pub fn stream(self, client: &JulesClient) -> Pin<Box<dyn Stream<Item = Result<Session>> + '_>> {
+        let state = QueryState {
+            sessions: client.stream_sessions(),
+            client,
+            query: self,
+            sources: None,
+            sorted: true,
+            last_create_time: None,
+            matched: 0,
+            done: false,
+        };
+        Box::pin(futures_util::stream::unfold(
+            state,
+            |mut state| async move {
+                let item = state.next().await?;
+                Some((item, state))
+            },
+        ))
+    }
+
+    /// Checks every condition, with `source` and `repo` resolved to `sources`.
+    fn matches(&self, session: &Session, sources: &HashSet<String>) -> bool {
+        if !self.states.is_empty()
+            && !session
+                .state
+                .as_ref()
+                .is_some_and(|s| self.states.contains(s))
+        {
+            return false;
+        }
+        if (!self.sources.is_empty() || !self.repos.is_empty())
+            && !sources.contains(&session.source_context.source)
+        {
+            return false;
+        }
+        if !in_range(session.create_time, self.created_after, self.created_before)
+            || !in_range(session.update_time, self.updated_after, self.updated_before)
+        {
+            return false;
+        }
+        if let Some(text) = &self.text {
+            let title = session.title.as_deref().unwrap_or_default().to_lowercase();
+            if !title.contains(text) && !session.prompt.to_lowercase().contains(text) {
+                return false;
+            }
+        }
+        if let Some(wanted) = self.has_pull_request {
+            let has = session.pull_requests().next().is_some();
+            if has != wanted {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Resolves the `source` and `repo` conditions to a set of source names.
+    async fn resolve_sources(&self, client: &JulesClient) -> Result<HashSet<String>> {
+        let mut sources: HashSet<String> = self.sources.iter().cloned().collect();
+        if self.repos.is_empty() {
+            return Ok(sources);
+        }
+        let mut all_sources = client.stream_sources();
+        while let Some(source) = all_sources.next().await {
+            let source = source?;
+            let Some(gh) = &source.github_repo else {
+                continue;
+            };
+            if self.repos.iter().any(|(owner, repo)| {
+                gh.owner.eq_ignore_ascii_case(owner) && gh.repo.eq_ignore_ascii_case(repo)
+            }) {
+                sources.insert(source.name);
+            }
+        }
+        Ok(sources)
+    }
+}
+
+struct QueryState<'a> {
+    sessions: Pin<Box<dyn Stream<Item = Result<Session>> + 'a>>,
+    client: &'a JulesClient,
+    query: SessionQuery,
+    sources: Option<HashSet<String>>,
+    sorted: bool,
+    last_create_time: Option<DateTime<Utc>>,
+    matched: usize,
+    done: bool,
+}
+
+impl QueryState<'_> {
+    async fn next(&mut self) -> Option<Result<Session>> {
+        if self.done || self.query.limit.is_some_and(|l| self.matched >= l) {
+            return None;
+        }
+        if self.sources.is_none() {
+            match self.query.resolve_sources(self.client).await {
+                Ok(sources) => self.sources = Some(sources),
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+        let sources = self.sources.as_ref()?;
+
+        while let Some(session) = self.sessions.next().await {
+            let session = match session {
+                Ok(s) => s,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            };
+
+            if let Some(created) = session.create_time {
+                if self.last_create_time.is_some_and(|last| created > last) {
+                    self.sorted = false;
+                }
+                self.last_create_time = Some(created);
+                let past_lower_bound = self
+                    .query
+                    .created_after
+                    .is_some_and(|after| created < after);
+                if past_lower_bound && self.sorted && self.query.newest_first {
+                    self.done = true;
+                    return None;
+                }
+            }
+
+            if self.query.matches(&session, sources) {
+                self.matched += 1;
+                return Some(Ok(session));
+            }
+        }
+        self.done = true;
+        None
+    }
+}
+
+fn in_range(
+    time: Option<DateTime<Utc>>,
+    after: Option<DateTime<Utc>>,
+    before: Option<DateTime<Utc>>,
+) -> bool {
+    if after.is_none() && before.is_none() {
+        return true;
+    }
+    let Some(time) = time else {
+        return false;
+    };
+    after.is_none_or(|a| time >= a) && before.is_none_or(|b| time < b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::{HttpTransport, Request, Response};
+    use async_trait::async_trait;
+    use chrono::TimeZone;
+    use serde_json::{Value, json};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Serves `sessions` one per page, in order, and one GitHub source.
+    struct Fake {
+        sessions: Vec<Value>,
+        pages: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl HttpTransport for Fake {
+        async fn send(&self, request: Request) -> Result<Response> {
+            let url = url::Url::parse(&request.uri().to_string())?;
+            let body = if url.path().ends_with("/sources") {
+                json!({ "sources": [{
+                    "name": "sources/github/acme/widgets",
+                    "id": "github/acme/widgets",
+                    "githubRepo": {
+                        "owner": "acme",
+                        "repo": "widgets",
+                        "isPrivate": false,
+                        "defaultBranch": { "displayName": "main" },
+                        "branches": [],
+                    },
+                }] })
+            } else {
+                self.pages.fetch_add(1, Ordering::SeqCst);
+                let index: usize = url
+                    .query_pairs()
+                    .find(|(k, _)| k == "pageToken")
+                    .map_or(0, |(_, v)| v.parse().unwrap());
+                let next = index + 1;
+                json!({
+                    "sessions": self.sessions.get(index).into_iter().collect::<Vec<_>>(),
+                    "nextPageToken": if next < self.sessions.len() { next.to_string() } else { String::new() },
+                })
+            };
+            Ok(Response::new(serde_json::to_vec(&body)?))
+        }
+    }
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 1, 1, hour, 0, 0).unwrap()
+    }
+
+    /// A session named `sessions/{id}`, created at `created` and updated an
+    /// hour later.
+    fn session(id: u32, created: u32) -> Value {
+        json!({
+            "name": format!("sessions/{id}"),
+            "prompt": format!("Prompt {id}"),
+            "title": format!("Title {id}"),
+            "state": "COMPLETED",
+            "sourceContext": { "source": "sources/github/acme/widgets" },
+            "createTime": at(created),
+            "updateTime": at(created + 1),
+        })
+    }
+
+    fn client(sessions: Vec<Value>) -> (JulesClient, Arc<AtomicUsize>) {
+        let pages = Arc::new(AtomicUsize::new(0));
+        let fake = Fake {
+            sessions,
+            pages: Arc::clone(&pages),
+        };
+        (JulesClient::new("key").unwrap().with_transport(fake), pages)
+    }
+
+    async fn ids(query: SessionQuery, client: &JulesClient) -> Vec<String> {
+        query
+            .run(client)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|s| s.name.unwrap().trim_start_matches("sessions/").to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn filters_by_state_source_and_repo() {
+        let mut failed = session(2, 2);
+        failed["state"] = json!("FAILED");
+        let mut other = session(3, 3);
+        other["sourceContext"]["source"] = json!("sources/github/acme/gadgets");
+        let (client, _) = client(vec![session(1, 1), failed, other]);
+
+        let q = SessionQuery::new().state(SessionState::Failed);
+        assert_eq!(ids(q, &client).await, ["2"]);
+        let q = SessionQuery::new().states([SessionState::Failed, SessionState::Completed]);
+        assert_eq!(ids(q, &client).await, ["1", "2", "3"]);
+        let q = SessionQuery::new().source("sources/github/acme/gadgets");
+        assert_eq!(ids(q, &client).await, ["3"]);
+        let q = SessionQuery::new().repo("ACME", "Widgets");
+        assert_eq!(ids(q, &client).await, ["1", "2"]);
+        let q = SessionQuery::new().repo("acme", "missing");
+        assert!(ids(q, &client).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn filters_by_text_and_pull_request() {
+        let mut with_pr = session(2, 2);
+        with_pr["outputs"] = json!([{ "pullRequest": {
+            "url": "https://github.com/acme/widgets/pull/1",
+            "title": "t",
+            "description": "",
+        } }]);
+        with_pr["prompt"] = json!("Fix the LOGIN handler");
+        let (client, _) = client(vec![session(1, 1), with_pr]);
+
+        let q = SessionQuery::new().text("login");
+        assert_eq!(ids(q, &client).await, ["2"]);
+        let q = SessionQuery::new().text("title 1");
+        assert_eq!(ids(q, &client).await, ["1"]);
+        let q = SessionQuery::new().has_pull_request(true);
+        assert_eq!(ids(q, &client).await, ["2"]);
+        let q = SessionQuery::new().has_pull_request(false);
+        assert_eq!(ids(q, &client).await, ["1"]);
+    }
+
+    #[tokio::test]
+    async fn time_ranges_include_the_lower_bound_only() {
+        let (client, _) = client(vec![session(1, 1), session(2, 2), session(3, 3)]);
+
+        let q = SessionQuery::new()
+            .created_after(at(2))
+            .created_before(at(3));
+        assert_eq!(ids(q, &client).await, ["2"]);
+        let q = SessionQuery::new().created_after(at(2));
+        assert_eq!(ids(q, &client).await, ["2", "3"]);
+        let q = SessionQuery::new()
+            .updated_after(at(3))
+            .updated_before(at(4));
+        assert_eq!(ids(q, &client).await, ["2"]);
+        let q = SessionQuery::new().updated_before(at(3));
+        assert_eq!(ids(q, &client).await, ["1"]);
+    }
+
+    #[tokio::test]
+    async fn limit_stops_paging() {
+        let (client, pages) = client(vec![session(1, 1), session(2, 2), session(3, 3)]);
+        let q = SessionQuery::new().limit(2);
+        assert_eq!(ids(q, &client).await, ["1", "2"]);
+        assert_eq!(pages.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn newest_first_stops_at_the_lower_bound() {
+        let sessions = vec![session(5, 5), session(4, 4), session(3, 3), session(1, 1)];
+        let (client, pages) = client([sessions, vec![session(6, 6)]].concat());
+
+        let q = SessionQuery::new()
+            .created_after(at(3))
+            .assume_newest_first();
+        assert_eq!(ids(q, &client).await, ["5", "4", "3"]);
+        assert_eq!(pages.load(Ordering::SeqCst), 4);
+
+        pages.store(0, Ordering::SeqCst);
+        let q = SessionQuery::new().created_after(at(3));
+        assert_eq!(ids(q, &client).await, ["5", "4", "3", "6"]);
+        assert_eq!(pages.load(Ordering::SeqCst), 5);
+    }
+
+    #[tokio::test]
+    async fn newest_first_keeps_scanning_when_out_of_order() {
+        let sessions = vec![session(5, 5), session(6, 6), session(1, 1), session(4, 4)];
+        let (client, pages) = client(sessions);
+        let q = SessionQuery::new()
+            .created_after(at(3))
+            .assume_newest_first();
+        assert_eq!(ids(q, &client).await, ["5", "6", "4"]);
+        assert_eq!(pages.load(Ordering::SeqCst), 4);
+    }
+}
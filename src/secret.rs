@@ -0,0 +1,120 @@
+//! A wrapper for credentials.
+
+use std::fmt;
+use std::str::FromStr;
+use zeroize::Zeroize;
+
+/// A credential, such as an API key or a GitHub token.
+///
+/// `Secret` hides its value: `Debug` and `Display` print `[REDACTED]`, so a
+/// secret stored in a struct cannot leak into logs through formatting. The
+/// value is overwritten with zeros when the secret is dropped. Use
+/// [`expose`](Self::expose) to read it.
+///
+/// # Example
+///
+/// ```rust
+/// use jules_rs::Secret;
+///
+/// let key = Secret::new("AIza-example");
+/// assert_eq!(format!("{key:?}"), "Secret([REDACTED])");
+/// assert_eq!(key.to_string(), "[REDACTED]");
+/// assert_eq!(key.expose(), "AIza-example");
+/// ```
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Secret(String);
+
+impl Secret {
+    /// Wraps a credential.
+    pub fn new(value: impl Into<String>) -> Self {
+        Self(value.into())
+    }
+
+    /// Returns the credential.
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns `true` if the credential is empty.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret([REDACTED])")
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for Secret {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl From<&String> for Secret {
+    fn from(value: &String) -> Self {
+        Self(value.clone())
+    }
+}
+
+impl FromStr for Secret {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::JulesClient;
+    use crate::chain::FollowUp;
+
+    const VALUE: &str = "ghp_value-that-must-not-leak";
+
+    #[test]
+    fn formatting_hides_the_value() {
+        let secret = Secret::new(VALUE);
+        assert!(!format!("{secret:?}").contains(VALUE));
+        assert!(!format!("{secret:#?}").contains(VALUE));
+        assert!(!secret.to_string().contains(VALUE));
+        assert_eq!(secret.expose(), VALUE);
+    }
+
+    #[test]
+    fn client_debug_hides_the_api_key() {
+        let client = JulesClient::new(VALUE).unwrap();
+        assert!(!format!("{client:?}").contains(VALUE));
+        let url = format!("https://proxy.example.com/{VALUE}/v1alpha/");
+        let client = JulesClient::with_base_url(VALUE, &url).unwrap();
+        let debug = format!("{client:#?}");
+        assert!(!debug.contains(VALUE), "{debug}");
+    }
+
+    #[test]
+    fn follow_up_debug_hides_the_github_token() {
+        let follow_up = FollowUp::new("Address the review").github_token(VALUE);
+        assert!(!format!("{follow_up:?}").contains(VALUE));
+    }
+}
@@ -0,0 +1,538 @@
+//! A local SQLite journal of sessions, activities, and sources.
+//!
+//! The sessions list endpoint offers no server-side filtering, so answering
+//! questions about history means paging through every session. A
+//! [`SessionStore`] mirrors the API into a local SQLite database and keeps it
+//! up to date incrementally: a session's activities are only re-fetched when
+//! its `update_time` has changed, and activities already in the journal are
+//! never rewritten.
+//!
+//! This module is only available with the `store` feature.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use jules_rs::{JulesClient, SessionState};
+//! use jules_rs::store::SessionStore;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = JulesClient::new("TOKEN")?;
+//! let store = SessionStore::open("jules.db")?;
+//!
+//! let report = store.sync(&client).await?;
+//! println!("{} sessions updated", report.sessions_updated);
+//!
+//! for session in store.sessions_with_state(&SessionState::Failed)? {
+//!     println!("{:?}", session.title);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::JulesClient;
+use crate::error::Result;
+use crate::models::{Activity, Session, SessionState, Source};
+use chrono::{DateTime, SecondsFormat, Utc};
+use futures_util::StreamExt;
+use rusqlite::{Connection, OptionalExtension, params};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS sessions (
+    name        TEXT PRIMARY KEY,
+    id          TEXT,
+    title       TEXT,
+    source      TEXT NOT NULL,
+    state       TEXT,
+    create_time TEXT,
+    update_time TEXT,
+    data        TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS sessions_update_time ON sessions (update_time);
+CREATE INDEX IF NOT EXISTS sessions_state ON sessions (state);
+
+CREATE TABLE IF NOT EXISTS activities (
+    name         TEXT PRIMARY KEY,
+    session_name TEXT NOT NULL,
+    id           TEXT NOT NULL,
+    create_time  TEXT NOT NULL,
+    originator   TEXT NOT NULL,
+    data         TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS activities_session ON activities (session_name, create_time);
+
+CREATE TABLE IF NOT EXISTS sources (
+    name  TEXT PRIMARY KEY,
+    id    TEXT NOT NULL,
+    owner TEXT,
+    repo  TEXT,
+    data  TEXT NOT NULL
+);
+";
+
+/// Counts of what changed during a [`SessionStore::sync`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    /// Number of sessions returned by the API.
+    pub sessions_seen: usize,
+    /// Number of sessions that were new or had a changed `update_time`.
+    pub sessions_updated: usize,
+    /// Number of activities added to the journal.
+    pub activities_added: usize,
+    /// Number of sources written to the journal.
+    pub sources_synced: usize,
+}
+
+/// A local SQLite mirror of sessions, activities, and sources.
+///
+/// Rows keep the full JSON of each resource alongside indexed columns
+/// (`state`, `source`, `create_time`, `update_time`, ...), so they can be
+/// queried with the helper methods or with arbitrary SQL through
+/// [`with_connection`](Self::with_connection). Timestamps are stored as
+/// RFC 3339 strings in UTC, which sort chronologically.
+pub struct SessionStore {
+    conn: Mutex<Connection>,
+}
+
+impl SessionStore {
+    /// Opens (or creates) a journal at the given path.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::with_conn(Connection::open(path)?)
+    }
+
+    /// Opens a journal held entirely in memory.
+    pub fn open_in_memory() -> Result<Self> {
+        Self::with_conn(Connection::open_in_memory()?)
+    }
+
+    fn with_conn(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Runs a closure with direct access to the underlying connection.
+    ///
+    /// Use this for queries the helper methods do not cover.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use jules_rs::store::SessionStore;
+    /// # fn example(store: &SessionStore) -> jules_rs::Result<()> {
+    /// let per_source: Vec<(String, i64)> = store.with_connection(|conn| {
+    ///     let mut stmt =
+    ///         conn.prepare("SELECT source, COUNT(*) FROM sessions GROUP BY source")?;
+    ///     let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    ///     rows.collect()
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_connection<T>(
+        &self,
+        f: impl FnOnce(&Connection) -> rusqlite::Result<T>,
+    ) -> Result<T> {
+        Ok(f(&self.conn())?)
+    }
+
+    // --- Sync ---
+
+    /// Brings the journal up to date with the API.
+    ///
+    /// All sources and sessions are listed. Activities are only fetched for
+    /// sessions that are new or whose `update_time` differs from the stored
+    /// one. Sessions deleted on the server are kept in the journal.
+    pub async fn sync(&self, client: &JulesClient) -> Result<SyncReport> {
+        let mut report = SyncReport {
+            sources_synced: self.sync_sources(client).await?,
+            ..Default::default()
+        };
+
+        let mut sessions = client.stream_sessions();
+        while let Some(session) = sessions.next().await {
+            let session = session?;
+            report.sessions_seen += 1;
+            if let Some(added) = self.sync_fetched_session(client, &session).await? {
+                report.sessions_updated += 1;
+                report.activities_added += added;
+            }
+        }
+        Ok(report)
+    }
+
+    /// Brings a single session and its activities up to date.
+    ///
+    /// # Returns
+    ///
+    /// The number of activities added to the journal.
+    pub async fn sync_session(&self, client: &JulesClient, session_name: &str) -> Result<usize> {
+        let session = client.get_session(session_name).await?;
+        Ok(self
+            .sync_fetched_session(client, &session)
+            .await?
+            .unwrap_or(0))
+    }
+
+    /// Writes every source returned by the API into the journal.
+    ///
+    /// # Returns
+    ///
+    /// The number of sources written.
+    pub async fn sync_sources(&self, client: &JulesClient) -> Result<usize> {
+        let mut count = 0;
+        let mut sources = client.stream_sources();
+        while let Some(source) = sources.next().await {
+            self.put_source(&source?)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Syncs a session already fetched from the API.
+    ///
+    /// Returns `None` if the stored copy is current, otherwise the number of
+    /// activities added.
+    async fn sync_fetched_session(
+        &self,
+        client: &JulesClient,
+        session: &Session,
+    ) -> Result<Option<usize>> {
+        let Some(name) = session.name.as_deref() else {
+            return Ok(None);
+        };
+        if session.update_time.is_some() && self.stored_update_time(name)? == session.update_time {
+            return Ok(None);
+        }
+
+        let mut added = 0;
+        let mut activities = client.stream_activities(name);
+        while let Some(activity) = activities.next().await {
+            if self.insert_activity(name, &activity?)? {
+                added += 1;
+            }
+        }
+        // Write the session last so an interrupted sync is retried next time.
+        self.put_session(session)?;
+        Ok(Some(added))
+    }
+
+    fn stored_update_time(&self, name: &str) -> Result<Option<DateTime<Utc>>> {
+        let stored: Option<Option<String>> = self
+            .conn()
+            .query_row(
+                "SELECT update_time FROM sessions WHERE name = ?1",
+                [name],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(stored
+            .flatten()
+            .and_then(|t| DateTime::parse_from_rfc3339(&t).ok())
+            .map(|t| t.with_timezone(&Utc)))
+    }
+
+    // --- Writes ---
+
+    /// Inserts or replaces a session.
+    ///
+    /// Sessions without a resource name are ignored.
+    pub fn put_session(&self, session: &Session) -> Result<()> {
+        let Some(name) = &session.name else {
+            return Ok(());
+        };
+        let data = serde_json::to_string(&session.to_json_value()?)?;
+        self.conn().execute(
+            "INSERT OR REPLACE INTO sessions
+                 (name, id, title, source, state, create_time, update_time, data)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                name,
+                session.id,
+                session.title,
+                session.source_context.source,
+                session.state.as_ref().map(SessionState::as_str),
+                session.create_time.map(timestamp),
+                session.update_time.map(timestamp),
+                data,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Inserts an activity if it is not already in the journal.
+    ///
+    /// Returns `true` if the activity was added.
+    pub fn insert_activity(&self, session_name: &str, activity: &Activity) -> Result<bool> {
+        let changed = self.conn().execute(
+            "INSERT OR IGNORE INTO activities
+                 (name, session_name, id, create_time, originator, data)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                activity.name,
+                session_name,
+                activity.id,
+                timestamp(activity.create_time),
+                activity.originator,
+                serde_json::to_string(activity)?,
+            ],
+        )?;
+        Ok(changed > 0)
+    }
+
+    /// Inserts or replaces a source.
+    pub fn put_source(&self, source: &Source) -> Result<()> {
+        let repo = source.github_repo.as_ref();
+        self.conn().execute(
+            "INSERT OR REPLACE INTO sources (name, id, owner, repo, data)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                source.name,
+                source.id,
+                repo.map(|r| &r.owner),
+                repo.map(|r| &r.repo),
+                serde_json::to_string(source)?,
+            ],
+        )?;
+        Ok(())
+    }
+
+    // --- Queries ---
+
+    /// Gets a stored session by its resource name.
+    pub fn session(&self, name: &str) -> Result<Option<Session>> {
+        Ok(self
+            .query_data("SELECT data FROM sessions WHERE name = ?1", [name])?
+            .pop())
+    }
+
+    /// Returns all stored sessions, most recently created first.
+    pub fn sessions(&self) -> Result<Vec<Session>> {
+        self.query_data("SELECT data FROM sessions ORDER BY create_time DESC", [])
+    }
+
+    /// Returns the stored sessions in a given state, most recently created first.
+    pub fn sessions_with_state(&self, state: &SessionState) -> Result<Vec<Session>> {
+        self.query_data(
+            "SELECT data FROM sessions WHERE state = ?1 ORDER BY create_time DESC",
+            [state.as_str()],
+        )
+    }
+
+    /// Returns the stored sessions for a source, most recently created first.
+    pub fn sessions_for_source(&self, source: &str) -> Result<Vec<Session>> {
+        self.query_data(
+            "SELECT data FROM sessions WHERE source = ?1 ORDER BY create_time DESC",
+            [source],
+        )
+    }
+
+    /// Returns the stored sessions updated at or after `since`, most recent first.
+    pub fn sessions_updated_since(&self, since: DateTime<Utc>) -> Result<Vec<Session>> {
+        self.query_data(
+            "SELECT data FROM sessions WHERE update_time >= ?1 ORDER BY update_time DESC",
+            [timestamp(since)],
+        )
+    }
+
+    /// Returns the stored activities of a session in chronological order.
+    pub fn activities(&self, session_name: &str) -> Result<Vec<Activity>> {
+        self.query_data(
+            "SELECT data FROM activities WHERE session_name = ?1 ORDER BY create_time, rowid",
+            [session_name],
+        )
+    }
+
+    /// Returns all stored sources.
+    pub fn sources(&self) -> Result<Vec<Source>> {
+        self.query_data("SELECT data FROM sources ORDER BY name", [])
+    }
+
+    fn query_data<T, P>(&self, sql: &str, params: P) -> Result<Vec<T>>
+    where
+        T: for<'de> serde::Deserialize<'de>,
+        P: rusqlite::Params,
+    {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt.query_map(params, |row| row.get::<_, String>(0))?;
+        let mut items = Vec::new();
+        for data in rows {
+            items.push(serde_json::from_str(&data?)?);
+        }
+        Ok(items)
+    }
+}
+
+/// Formats a time with a fixed number of fractional digits, so stored times
+/// sort chronologically and compare equal to the API's nanosecond times.
+fn timestamp(t: DateTime<Utc>) -> String {
+    t.to_rfc3339_opts(SecondsFormat::Nanos, true)
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::models::SourceContext;
+    use crate::testing::{MockServer, Script};
+    use chrono::TimeZone;
+
+    fn session(prompt: &str) -> Session {
+        Session {
+            name: None,
+            id: None,
+            prompt: prompt.to_string(),
+            source_context: SourceContext {
+                source: "sources/github/acme/widgets".to_string(),
+                github_repo_context: None,
+            },
+            title: Some(format!("Title {prompt}")),
+            require_plan_approval: None,
+            automation_mode: None,
+            create_time: None,
+            update_time: None,
+            state: None,
+            url: None,
+            outputs: None,
+        }
+    }
+
+    fn activity_requests(server: &MockServer) -> usize {
+        server
+            .requests()
+            .iter()
+            .filter(|r| r.path.ends_with("/activities"))
+            .count()
+    }
+
+    #[tokio::test]
+    async fn unchanged_sessions_are_not_refetched() {
+        let server = MockServer::start();
+        server.add_github_source("acme", "widgets", &["main"]);
+        let name = server.insert_session(session("a")).name.unwrap();
+        server.apply(
+            &name,
+            &Script::new().agent_message("hi").progress("working"),
+        );
+        let client = server.client();
+        let store = SessionStore::open_in_memory().unwrap();
+
+        let report = store.sync(&client).await.unwrap();
+        assert_eq!(
+            report,
+            SyncReport {
+                sessions_seen: 1,
+                sessions_updated: 1,
+                activities_added: 2,
+                sources_synced: 1,
+            }
+        );
+        assert_eq!(activity_requests(&server), 1);
+
+        let report = store.sync(&client).await.unwrap();
+        assert_eq!(report.sessions_updated, 0);
+        assert_eq!(report.activities_added, 0);
+        assert_eq!(activity_requests(&server), 1);
+        assert_eq!(store.sources().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn resyncing_does_not_duplicate_activities() {
+        let server = MockServer::start();
+        let name = server.insert_session(session("a")).name.unwrap();
+        server.apply(&name, &Script::new().agent_message("one"));
+        let client = server.client();
+        let store = SessionStore::open_in_memory().unwrap();
+        assert_eq!(store.sync_session(&client, &name).await.unwrap(), 1);
+
+        server.apply(&name, &Script::new().agent_message("two"));
+        let report = store.sync(&client).await.unwrap();
+        assert_eq!(report.sessions_updated, 1);
+        assert_eq!(report.activities_added, 1);
+        assert_eq!(activity_requests(&server), 2);
+
+        let ids: Vec<String> = store
+            .activities(&name)
+            .unwrap()
+            .into_iter()
+            .map(|a| a.id)
+            .collect();
+        assert_eq!(ids, ["1", "2"]);
+        for activity in server.activities(&name) {
+            assert!(!store.insert_activity(&name, &activity).unwrap());
+        }
+        assert_eq!(store.activities(&name).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn sessions_round_trip_and_query() {
+        let store = SessionStore::open_in_memory().unwrap();
+        let at = |h| Utc.with_ymd_and_hms(2025, 1, 1, h, 0, 0).unwrap();
+        let mut old = session("old");
+        old.name = Some("sessions/1".to_string());
+        old.id = Some("1".to_string());
+        old.state = Some(SessionState::Failed);
+        old.create_time = Some(at(1));
+        old.update_time = Some(at(2));
+        old.url = Some("https://jules.google.com/session/1".to_string());
+        let mut new = session("new");
+        new.name = Some("sessions/2".to_string());
+        new.source_context.source = "sources/github/acme/gadgets".to_string();
+        new.state = Some(SessionState::Completed);
+        new.create_time = Some(at(3));
+        new.update_time = Some(at(4));
+        store.put_session(&old).unwrap();
+        store.put_session(&new).unwrap();
+        store.put_session(&session("unnamed")).unwrap();
+
+        let stored = store.session("sessions/1").unwrap().unwrap();
+        assert_eq!(
+            stored.to_json_value().unwrap(),
+            old.to_json_value().unwrap()
+        );
+        assert!(store.session("sessions/3").unwrap().is_none());
+
+        let names = |sessions: Vec<Session>| -> Vec<String> {
+            sessions.into_iter().filter_map(|s| s.name).collect()
+        };
+        assert_eq!(
+            names(store.sessions().unwrap()),
+            ["sessions/2", "sessions/1"]
+        );
+        assert_eq!(
+            names(store.sessions_with_state(&SessionState::Failed).unwrap()),
+            ["sessions/1"]
+        );
+        assert_eq!(
+            names(
+                store
+                    .sessions_for_source("sources/github/acme/gadgets")
+                    .unwrap()
+            ),
+            ["sessions/2"]
+        );
+        assert_eq!(
+            names(store.sessions_updated_since(at(2)).unwrap()),
+            ["sessions/2", "sessions/1"]
+        );
+        assert_eq!(
+            names(store.sessions_updated_since(at(3)).unwrap()),
+            ["sessions/2"]
+        );
+
+        old.state = Some(SessionState::Completed);
+        store.put_session(&old).unwrap();
+        assert!(
+            store
+                .sessions_with_state(&SessionState::Failed)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,842 @@
+//! Named prompt templates with typed variables and includes.
+//!
+//! Long prompts shared across teams drift when they are copy-pasted into
+//! [`Session::prompt`](crate::Session::prompt) or
+//! [`send_message`](crate::JulesClient::send_message). A [`PromptTemplate`]
+//! keeps such a prompt in one place, and a [`TemplateLibrary`] collects
+//! templates so they can include each other, for example a shared coding
+//! standards preamble.
+//!
+//! # Syntax
+//!
+//! | Syntax | Meaning |
+//! |--------|---------|
+//! | `{{name}}` | The value of the variable `name`, of any type |
+//! | `{{name: int}}` | A variable that must be an `int`, `bool`, `list` or `string` |
+//! | `{{> preamble}}` | The rendered text of the template named `preamble` |
+//! | `\{{` | A literal `{{`, e.g. `$\{{ matrix.os }}` renders as `${{ matrix.os }}` |
+//!
+//! Lists render as one `- item` line per element. Rendering fails with
+//! [`JulesError::Template`] if any variable is missing or has the wrong
+//! type, naming every missing variable at once.
+//!
+//! # Example
+//!
+//! ```rust
+//! use jules_rs::template::{PromptTemplate, TemplateLibrary, TemplateVars};
+//!
+//! # fn example() -> jules_rs::Result<()> {
+//! let mut library = TemplateLibrary::new();
+//! library.insert(PromptTemplate::new(
+//!     "standards",
+//!     "Follow the {{team}} coding standards.",
+//! )?);
+//! library.insert(PromptTemplate::new(
+//!     "fix-issue",
+//!     "{{> standards}}\nFix issue #{{issue: int}}. Touch only:\n{{files: list}}",
+//! )?);
+//!
+//! let vars = TemplateVars::new()
+//!     .set("team", "platform")
+//!     .set("issue", 42)
+//!     .set("files", vec!["src/lib.rs", "src/client.rs"]);
+//! let prompt = library.render("fix-issue", &vars)?;
+//! assert_eq!(
+//!     prompt,
+//!     "Follow the platform coding standards.\nFix issue #42. Touch only:\n- src/lib.rs\n- src/client.rs"
+//! );
+//! # Ok(())
+//! # }
+//! # example().unwrap();
+//! ```
+//!
+//! Templates can also be loaded from a directory, where each file becomes a
+//! template named after its path:
+//!
+//! ```rust,no_run
+//! use jules_rs::template::{TemplateLibrary, TemplateVars};
+//!
+//! # fn example() -> jules_rs::Result<()> {
+//! // prompts/standards.md, prompts/rust/migrate.md, ...
+//! let library = TemplateLibrary::from_dir("prompts")?;
+//! let prompt = library.render("rust/migrate", &TemplateVars::new().set("crate", "widgets"))?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::{JulesError, Result};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+/// File extensions loaded by [`TemplateLibrary::load_dir`].
+const EXTENSIONS: &[&str] = &["md", "txt", "tmpl"];
+
+/// The type of a template variable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VarType {
+    /// Any value, rendered as text.
+    String,
+    /// A whole number.
+    Int,
+    /// `true` or `false`.
+    Bool,
+    /// A list of strings, rendered as `- item` lines.
+    List,
+}
+
+impl VarType {
+    /// Returns the name used in placeholders, such as `int`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Int => "int",
+            Self::Bool => "bool",
+            Self::List => "list",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "string" | "str" => Some(Self::String),
+            "int" | "integer" => Some(Self::Int),
+            "bool" | "boolean" => Some(Self::Bool),
+            "list" => Some(Self::List),
+            _ => None,
+        }
+    }
+}
+
+/// The value of a template variable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateValue {
+    /// Text.
+    String(String),
+    /// A whole number.
+    Int(i64),
+    /// A boolean.
+    Bool(bool),
+    /// A list of strings.
+    List(Vec<String>),
+}
+
+impl TemplateValue {
+    /// Whether this value can fill a placeholder of the given type.
+    ///
+    /// Strings are accepted for `int` and `bool` placeholders when they
+    /// parse as one, so values read from text files can be used as is.
+    pub fn matches(&self, ty: VarType) -> bool {
+        match (self, ty) {
+            (_, VarType::String) => true,
+            (Self::Int(_), VarType::Int) => true,
+            (Self::Bool(_), VarType::Bool) => true,
+            (Self::List(_), VarType::List) => true,
+            (Self::String(s), VarType::Int) => s.trim().parse::<i64>().is_ok(),
+            (Self::String(s), VarType::Bool) => s.trim().parse::<bool>().is_ok(),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for TemplateValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::String(s) => f.write_str(s),
+            Self::Int(n) => write!(f, "{n}"),
+            Self::Bool(b) => write!(f, "{b}"),
+            Self::List(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str("\n")?;
+                    }
+                    write!(f, "- {item}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl From<String> for TemplateValue {
+    fn from(s: String) -> Self {
+        Self::String(s)
+    }
+}
+
+impl From<&str> for TemplateValue {
+    fn from(s: &str) -> Self {
+        Self::String(s.to_string())
+    }
+}
+
+impl From<i64> for TemplateValue {
+    fn from(n: i64) -> Self {
+        Self::Int(n)
+    }
+}
+
+impl From<i32> for TemplateValue {
+    fn from(n: i32) -> Self {
+        Self::Int(n.into())
+    }
+}
+
+impl From<u32> for TemplateValue {
+    fn from(n: u32) -> Self {
+        Self::Int(n.into())
+    }
+}
+
+impl From<bool> for TemplateValue {
+    fn from(b: bool) -> Self {
+        Self::Bool(b)
+    }
+}
+
+impl<T: Into<String>> From<Vec<T>> for TemplateValue {
+    fn from(items: Vec<T>) -> Self {
+        Self::List(items.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Variables used to render a template.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TemplateVars {
+    values: BTreeMap<String, TemplateValue>,
+}
+
+impl TemplateVars {
+    /// Creates an empty set of variables.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a variable, replacing any previous value.
+    pub fn set(mut self, name: impl Into<String>, value: impl Into<TemplateValue>) -> Self {
+        self.insert(name, value);
+        self
+    }
+
+    /// Sets a variable in place, replacing any previous value.
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<TemplateValue>) {
+        self.values.insert(name.into(), value.into());
+    }
+
+    /// Returns the value of a variable.
+    pub fn get(&self, name: &str) -> Option<&TemplateValue> {
+        self.values.get(name)
+    }
+
+    /// Whether no variables are set.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+impl<K: Into<String>, V: Into<TemplateValue>> FromIterator<(K, V)> for TemplateVars {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut vars = Self::new();
+        vars.extend(iter);
+        vars
+    }
+}
+
+impl<K: Into<String>, V: Into<TemplateValue>> Extend<(K, V)> for TemplateVars {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (name, value) in iter {
+            self.insert(name, value);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Text(String),
+    Var { name: String, ty: VarType },
+    Include(String),
+}
+
+/// A named prompt template.
+///
+/// The template is parsed when it is created, so syntax errors such as an
+/// unclosed `{{` or an unknown type are reported up front. See the
+/// [module documentation](self) for the syntax.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptTemplate {
+    name: String,
+    source: String,
+    segments: Vec<Segment>,
+}
+
+impl PromptTemplate {
+    /// Parses a template.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JulesError::Template`] if the template is malformed or
+    /// uses a variable with two different types.
+    pub fn new(name: impl Into<String>, source: impl Into<String>) -> Result<Self> {
+        let name = name.into();
+        let source = source.into();
+        let segments = parse(&source).map_err(|e| template_error(&name, e))?;
+        let mut vars = BTreeMap::new();
+        for segment in &segments {
+            if let Segment::Var { name: var, ty } = segment {
+                merge_type(&mut vars, var, *ty).map_err(|e| template_error(&name, e))?;
+            }
+        }
+        Ok(Self {
+            name,
+            source,
+            segments,
+        })
+    }
+
+    /// Returns the template name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the unrendered template text.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns the variables used directly by this template and their types.
+    ///
+    /// Variables of included templates are not listed; use
+    /// [`TemplateLibrary::variables`] for those.
+    pub fn variables(&self) -> BTreeMap<&str, VarType> {
+        let mut vars = BTreeMap::new();
+        for segment in &self.segments {
+            if let Segment::Var { name, ty } = segment {
+                // Conflicting types are rejected when the template is parsed.
+                let _ = merge_type(&mut vars, name, *ty);
+            }
+        }
+        vars
+    }
+
+    /// Returns the names of the templates this template includes.
+    pub fn includes(&self) -> impl Iterator<Item = &str> {
+        self.segments.iter().filter_map(|s| match s {
+            Segment::Include(name) => Some(name.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Renders a template that has no includes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JulesError::Template`] if a variable is missing or has the
+    /// wrong type, or if the template includes another template. Render
+    /// templates with includes through a [`TemplateLibrary`].
+    pub fn render(&self, vars: &TemplateVars) -> Result<String> {
+        if let Some(include) = self.includes().next() {
+            return Err(template_error(
+                &self.name,
+                format!("includes `{include}`; render it through a TemplateLibrary"),
+            ));
+        }
+        check_vars(&self.name, &self.variables(), vars)?;
+        let mut out = String::with_capacity(self.source.len());
+        write_segments(self, vars, None, &mut Vec::new(), &mut out)?;
+        Ok(out)
+    }
+}
+
+/// A collection of named templates that can include each other.
+#[derive(Debug, Clone, Default)]
+pub struct TemplateLibrary {
+    templates: BTreeMap<String, PromptTemplate>,
+}
+
+impl TemplateLibrary {
+    /// Creates an empty library.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a library from the templates in a directory.
+    ///
+    /// See [`load_dir`](Self::load_dir).
+    pub fn from_dir(dir: impl AsRef<Path>) -> Result<Self> {
+        let mut library = Self::new();
+        library.load_dir(dir)?;
+        Ok(library)
+    }
+
+    /// Adds every `.md`, `.txt` and `.tmpl` file under a directory.
+    ///
+    /// Subdirectories are searched too. Each template is named after its
+    /// path relative to `dir`, without the extension and with `/` as the
+    /// separator, so `dir/rust/migrate.md` becomes `rust/migrate`. Hidden
+    /// files and directories are skipped. Once loaded, includes are checked
+    /// with [`validate`](Self::validate).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JulesError::Io`] if the directory cannot be read, or
+    /// [`JulesError::Template`] if a template is malformed or includes a
+    /// template that does not exist.
+    pub fn load_dir(&mut self, dir: impl AsRef<Path>) -> Result<()> {
+        self.load_dir_at(dir.as_ref(), "")?;
+        self.validate()
+    }
+
+    fn load_dir_at(&mut self, dir: &Path, prefix: &str) -> Result<()> {
+        let mut entries = std::fs::read_dir(dir)?.collect::<std::io::Result<Vec<_>>>()?;
+        entries.sort_by_key(|e| e.file_name());
+        for entry in entries {
+            let path = entry.path();
+            let file_name = entry.file_name();
+            let Some(file_name) = file_name.to_str() else {
+                continue;
+            };
+            if file_name.starts_with('.') {
+                continue;
+            }
+            if entry.file_type()?.is_dir() {
+                self.load_dir_at(&path, &format!("{prefix}{file_name}/"))?;
+                continue;
+            }
+            let extension = path.extension().and_then(|e| e.to_str());
+            if !extension.is_some_and(|e| EXTENSIONS.contains(&e)) {
+                continue;
+            }
+            let stem = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or(file_name);
+            let source = std::fs::read_to_string(&path)?;
+            self.insert(PromptTemplate::new(format!("{prefix}{stem}"), source)?);
+        }
+        Ok(())
+    }
+
+    /// Adds a template, replacing any template with the same name.
+    pub fn insert(&mut self, template: PromptTemplate) {
+        self.templates.insert(template.name.clone(), template);
+    }
+
+    /// Returns a template by name.
+    pub fn get(&self, name: &str) -> Option<&PromptTemplate> {
+        self.templates.get(name)
+    }
+
+    /// Returns the names of all templates, in order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.templates.keys().map(String::as_str)
+    }
+
+    /// Checks that every include refers to an existing template and that no
+    /// template includes itself, directly or indirectly.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JulesError::Template`] describing the first problem found.
+    pub fn validate(&self) -> Result<()> {
+        for name in self.templates.keys() {
+            self.variables(name)?;
+        }
+        Ok(())
+    }
+
+    /// Returns every variable needed to render a template, including the
+    /// variables of the templates it includes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JulesError::Template`] if the template or one of its
+    /// includes does not exist, if includes form a cycle, or if a variable
+    /// is used with two different types.
+    pub fn variables(&self, name: &str) -> Result<BTreeMap<&str, VarType>> {
+        self.template_variables(self.lookup(name, &[])?)
+    }
+
+    fn template_variables<'a>(
+        &'a self,
+        template: &'a PromptTemplate,
+    ) -> Result<BTreeMap<&'a str, VarType>> {
+        let mut vars = BTreeMap::new();
+        self.collect_variables(template, &mut Vec::new(), &mut vars)?;
+        Ok(vars)
+    }
+
+    fn collect_variables<'a>(
+        &'a self,
+        template: &'a PromptTemplate,
+        stack: &mut Vec<&'a str>,
+        vars: &mut BTreeMap<&'a str, VarType>,
+    ) -> Result<()> {
+        stack.push(&template.name);
+        for segment in &template.segments {
+            match segment {
+                Segment::Var { name: var, ty } => {
+                    merge_type(vars, var, *ty).map_err(|e| template_error(&template.name, e))?
+                }
+                Segment::Include(include) => {
+                    let included = self.lookup(include, stack)?;
+                    self.collect_variables(included, stack, vars)?
+                }
+                Segment::Text(_) => {}
+            }
+        }
+        stack.pop();
+        Ok(())
+    }
+
+    fn lookup<'a>(&'a self, name: &str, stack: &[&str]) -> Result<&'a PromptTemplate> {
+        if stack.contains(&name) {
+            let mut cycle = stack.join(" > ");
+            cycle.push_str(&format!(" > {name}"));
+            return Err(template_error(name, format!("include cycle: {cycle}")));
+        }
+        self.templates.get(name).ok_or_else(|| match stack.last() {
+            Some(parent) => template_error(parent, format!("includes unknown template `{name}`")),
+            None => JulesError::Template(format!("unknown template `{name}`")),
+        })
+    }
+
+    /// Checks that `vars` contains every variable a template needs, with
+    /// the right types, without rendering it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JulesError::Template`] listing every missing variable, or
+    /// the first variable with the wrong type.
+    pub fn check(&self, name: &str, vars: &TemplateVars) -> Result<()> {
+        check_vars(name, &self.variables(name)?, vars)
+    }
+
+    /// Renders a template and everything it includes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JulesError::Template`] if the template cannot be
+    /// [checked](Self::check).
+    pub fn render(&self, name: &str, vars: &TemplateVars) -> Result<String> {
+        self.render_template(self.lookup(name, &[])?, vars)
+    }
+
+    /// Renders a template that is not part of the library, resolving its
+    /// includes from the library.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JulesError::Template`] if an include does not exist or a
+    /// variable is missing or has the wrong type.
+    pub fn render_template(
+        &self,
+        template: &PromptTemplate,
+        vars: &TemplateVars,
+    ) -> Result<String> {
+        check_vars(&template.name, &self.template_variables(template)?, vars)?;
+        let mut out = String::with_capacity(template.source.len());
+        write_segments(template, vars, Some(self), &mut Vec::new(), &mut out)?;
+        Ok(out)
+    }
+}
+
+fn template_error(name: &str, message: impl fmt::Display) -> JulesError {
+    JulesError::Template(format!("template `{name}`: {message}"))
+}
+
+/// Records a variable's type, preferring a specific type over `string`.
+fn merge_type<'a>(
+    vars: &mut BTreeMap<&'a str, VarType>,
+    name: &'a str,
+    ty: VarType,
+) -> std::result::Result<(), String> {
+    let entry = vars.entry(name).or_insert(ty);
+    if *entry == VarType::String {
+        *entry = ty;
+    } else if *entry != ty && ty != VarType::String {
+        return Err(format!(
+            "variable `{name}` is used as both {} and {}",
+            entry.as_str(),
+            ty.as_str()
+        ));
+    }
+    Ok(())
+}
+
+fn check_vars(name: &str, needed: &BTreeMap<&str, VarType>, vars: &TemplateVars) -> Result<()> {
+    let missing: Vec<_> = needed
+        .keys()
+        .filter(|var| vars.get(var).is_none())
+        .map(|var| format!("`{var}`"))
+        .collect();
+    if !missing.is_empty() {
+        let noun = if missing.len() == 1 {
+            "variable"
+        } else {
+            "variables"
+        };
+        return Err(template_error(
+            name,
+            format!("missing {noun} {}", missing.join(", ")),
+        ));
+    }
+    for (var, ty) in needed {
+        if let Some(value) = vars.get(var) {
+            if !value.matches(*ty) {
+                return Err(template_error(
+                    name,
+                    format!("variable `{var}` must be {}, got `{value}`", ty.as_str()),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_segments<'a>(
+    template: &'a PromptTemplate,
+    vars: &TemplateVars,
+    library: Option<&'a TemplateLibrary>,
+    stack: &mut Vec<&'a str>,
+    out: &mut String,
+) -> Result<()> {
+    stack.push(&template.name);
+    for segment in &template.segments {
+        match segment {
+            Segment::Text(text) => out.push_str(text),
+            Segment::Var { name, .. } => {
+                let value = vars.get(name).ok_or_else(|| {
+                    template_error(&template.name, format!("missing variable `{name}`"))
+                })?;
+                out.push_str(&value.to_string());
+            }
+            Segment::Include(include) => {
+                let library = library.ok_or_else(|| {
+                    template_error(&template.name, format!("cannot include `{include}`"))
+                })?;
+                let included = library.lookup(include, stack)?;
+                // Included files usually end with a newline that would
+                // otherwise double up with the one after the placeholder.
+                let start = out.len();
+                write_segments(included, vars, Some(library), stack, out)?;
+                if out[start..].ends_with('\n') {
+                    out.pop();
+                }
+            }
+        }
+    }
+    stack.pop();
+    Ok(())
+}
+
+fn parse(source: &str) -> std::result::Result<Vec<Segment>, String> {
+    let mut segments = Vec::new();
+    let mut text = String::new();
+    let mut rest = source;
+    while let Some(start) = rest.find("{{") {
+        // `\{{` is a literal `{{`, e.g. for GitHub Actions expressions.
+        if let Some(before) = rest[..start].strip_suffix('\\') {
+            text.push_str(before);
+            text.push_str("{{");
+            rest = &rest[start + 2..];
+            continue;
+        }
+        text.push_str(&rest[..start]);
+        if !text.is_empty() {
+            segments.push(Segment::Text(std::mem::take(&mut text)));
+        }
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| "unclosed `{{`".to_string())?;
+        let tag = after[..end].trim();
+        if let Some(include) = tag.strip_prefix('>') {
+            let include = include.trim();
+            if include.is_empty() {
+                return Err("`{{>}}` needs a template name".to_string());
+            }
+            segments.push(Segment::Include(include.to_string()));
+        } else {
+            let (name, ty) = match tag.split_once(':') {
+                Some((name, ty)) => {
+                    let ty = ty.trim();
+                    let ty = VarType::parse(ty).ok_or_else(|| {
+                        format!("unknown type `{ty}`; expected string, int, bool or list")
+                    })?;
+                    (name.trim(), ty)
+                }
+                None => (tag, VarType::String),
+            };
+            if name.is_empty() || name.contains(char::is_whitespace) {
+                return Err(format!("invalid variable name `{name}`"));
+            }
+            segments.push(Segment::Var {
+                name: name.to_string(),
+                ty,
+            });
+        }
+        rest = &after[end + 2..];
+    }
+    text.push_str(rest);
+    if !text.is_empty() {
+        segments.push(Segment::Text(text));
+    }
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(source: &str) -> PromptTemplate {
+        PromptTemplate::new("t", source).unwrap()
+    }
+
+    fn error(result: Result<impl fmt::Debug>) -> String {
+        match result {
+            Err(JulesError::Template(message)) => message,
+            other => panic!("expected a template error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn renders_variables() {
+        let vars = TemplateVars::new().set("name", "world").set("n", 3);
+        assert_eq!(
+            template("Hello {{name}}, {{ n : int }} times")
+                .render(&vars)
+                .unwrap(),
+            "Hello world, 3 times"
+        );
+    }
+
+    #[test]
+    fn renders_lists_and_bools() {
+        let vars = TemplateVars::new()
+            .set("files", vec!["a.rs", "b.rs"])
+            .set("strict", true);
+        assert_eq!(
+            template("{{files: list}}\nstrict={{strict: bool}}")
+                .render(&vars)
+                .unwrap(),
+            "- a.rs\n- b.rs\nstrict=true"
+        );
+    }
+
+    #[test]
+    fn escaped_braces_are_literal() {
+        let t = template("runs-on: $\\{{ matrix.os }} for {{name}}; format!(\"\\{{}}\")");
+        assert_eq!(t.variables().keys().collect::<Vec<_>>(), [&"name"]);
+        assert_eq!(
+            t.render(&TemplateVars::new().set("name", "ci")).unwrap(),
+            "runs-on: ${{ matrix.os }} for ci; format!(\"{{}}\")"
+        );
+    }
+
+    #[test]
+    fn reports_every_missing_variable() {
+        let message = error(template("{{a}} {{b}} {{c}}").render(&TemplateVars::new().set("b", 1)));
+        assert_eq!(message, "template `t`: missing variables `a`, `c`");
+    }
+
+    #[test]
+    fn checks_types() {
+        let message =
+            error(template("#{{issue: int}}").render(&TemplateVars::new().set("issue", "x")));
+        assert_eq!(
+            message,
+            "template `t`: variable `issue` must be int, got `x`"
+        );
+    }
+
+    #[test]
+    fn rejects_conflicting_types() {
+        let message = error(PromptTemplate::new("t", "{{a: int}} {{a: bool}}"));
+        assert_eq!(
+            message,
+            "template `t`: variable `a` is used as both int and bool"
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_templates() {
+        assert!(error(PromptTemplate::new("t", "{{a")).contains("unclosed `{{`"));
+        assert!(error(PromptTemplate::new("t", "{{a: float}}")).contains("unknown type `float`"));
+        assert!(error(PromptTemplate::new("t", "{{>}}")).contains("needs a template name"));
+        assert!(error(PromptTemplate::new("t", "{{a b}}")).contains("invalid variable name"));
+    }
+
+    #[test]
+    fn includes_trim_one_trailing_newline() {
+        let mut library = TemplateLibrary::new();
+        library.insert(PromptTemplate::new("preamble", "Be careful, {{team}}.\n").unwrap());
+        library.insert(PromptTemplate::new("main", "{{> preamble}}\nFix {{issue: int}}.").unwrap());
+        let vars = TemplateVars::new().set("team", "platform").set("issue", 7);
+        assert_eq!(
+            library.render("main", &vars).unwrap(),
+            "Be careful, platform.\nFix 7."
+        );
+        assert_eq!(
+            library.variables("main").unwrap(),
+            BTreeMap::from([("issue", VarType::Int), ("team", VarType::String)])
+        );
+    }
+
+    #[test]
+    fn include_needs_a_library() {
+        let message = error(template("{{> other}}").render(&TemplateVars::new()));
+        assert!(message.contains("render it through a TemplateLibrary"));
+    }
+
+    #[test]
+    fn detects_include_cycles() {
+        let mut library = TemplateLibrary::new();
+        library.insert(PromptTemplate::new("a", "{{> b}}").unwrap());
+        library.insert(PromptTemplate::new("b", "{{> c}}").unwrap());
+        library.insert(PromptTemplate::new("c", "{{> a}}").unwrap());
+        let message = error(library.validate());
+        assert!(
+            message.contains("include cycle: a > b > c > a"),
+            "{message}"
+        );
+    }
+
+    #[test]
+    fn detects_unknown_includes() {
+        let mut library = TemplateLibrary::new();
+        library.insert(PromptTemplate::new("a", "{{> missing}}").unwrap());
+        assert_eq!(
+            error(library.validate()),
+            "template `a`: includes unknown template `missing`"
+        );
+        assert_eq!(
+            error(library.render("nope", &TemplateVars::new())),
+            "unknown template `nope`"
+        );
+    }
+
+    #[test]
+    fn loads_templates_from_a_directory() {
+        let dir = std::env::temp_dir().join(format!("jules-templates-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("rust")).unwrap();
+        std::fs::create_dir_all(dir.join(".hidden")).unwrap();
+        std::fs::write(dir.join("standards.md"), "Use {{style}}.\n").unwrap();
+        std::fs::write(dir.join("rust/migrate.txt"), "{{> standards}}\nMigrate.").unwrap();
+        std::fs::write(dir.join("notes.json"), "{}").unwrap();
+        std::fs::write(dir.join(".hidden/secret.md"), "x").unwrap();
+
+        let library = TemplateLibrary::from_dir(&dir);
+        let _ = std::fs::remove_dir_all(&dir);
+        let library = library.unwrap();
+        assert_eq!(
+            library.names().collect::<Vec<_>>(),
+            ["rust/migrate", "standards"]
+        );
+        assert_eq!(
+            library
+                .render("rust/migrate", &TemplateVars::new().set("style", "rustfmt"))
+                .unwrap(),
+            "Use rustfmt.\nMigrate."
+        );
+    }
+}
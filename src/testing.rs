@@ -0,0 +1,1516 @@
+//! An in-process mock of the Jules API for tests.
+//!
+//! [`MockServer`] starts a local HTTP server that implements every endpoint
+//! of the Jules API: sessions (create, get, list, delete, `sendMessage`,
+//! `approvePlan`), activities (get, list), and sources (get, list, with
+//! `name=` filters). State is kept in memory and list endpoints paginate like
+//! the real API, so code written against [`JulesClient`] can be tested
+//! without network access.
+//!
+//! What happens to a session after an API call is described with a
+//! [`Script`] registered for an [`Event`]: for example, emit a plan when a
+//! session is created, and emit progress and complete the session once the
+//! plan is approved.
+//!
+//! Failures can be injected with [`FaultRule`]s, to test how code copes
+//! with rate limiting, server errors, slow or truncated responses, expired
+//! credentials, and broken pagination:
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use jules_rs::JulesError;
+//! use jules_rs::testing::{Fault, FaultRule, MockServer};
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let server = MockServer::start();
+//! server.inject(
+//!     FaultRule::new(Fault::RateLimited { retry_after: Duration::from_secs(2) }).times(1),
+//! );
+//! server.inject(FaultRule::new(Fault::TruncatedJson).path("sources").times(1));
+//!
+//! let client = server.client();
+//! match client.list_sessions(None, None).await {
+//!     Err(JulesError::Api { status, .. }) => assert_eq!(status.as_u16(), 429),
+//!     other => panic!("unexpected {other:?}"),
+//! }
+//! assert!(client.list_sessions(None, None).await.is_ok());
+//! assert!(matches!(
+//!     client.list_sources(None, None, None).await,
+//!     Err(JulesError::Serialization(_))
+//! ));
+//! # }
+//! ```
+//!
+//! This module is only available with the `mock` feature.
+//!
+//! # Example
+//!
+//! ```rust
+//! use jules_rs::{GitHubRepoContext, Session, SessionState, SourceContext};
+//! use jules_rs::testing::{Event, MockServer, Script};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let server = MockServer::start();
+//! let source = server.add_github_source("acme", "widgets", &["main"]);
+//! server.on(Event::Create, Script::new().plan(&["Read the code", "Fix the bug"]));
+//! server.on(
+//!     Event::ApprovePlan,
+//!     Script::new()
+//!         .progress("Fixing the bug")
+//!         .pull_request("https://github.com/acme/widgets/pull/7", "Fix the bug")
+//!         .complete(),
+//! );
+//!
+//! let client = server.client();
+//! let session = client
+//!     .create_session(&Session {
+//!         name: None,
+//!         id: None,
+//!         prompt: "Fix the bug".to_string(),
+//!         source_context: SourceContext {
+//!             source: source.name.clone(),
+//!             github_repo_context: Some(GitHubRepoContext {
+//!                 starting_branch: "main".to_string(),
+//!             }),
+//!         },
+//!         title: None,
+//!         require_plan_approval: Some(true),
+//!         automation_mode: None,
+//!         create_time: None,
+//!         update_time: None,
+//!         state: None,
+//!         url: None,
+//!         outputs: None,
+//!     })
+//!     .await?;
+//! let name = session.name.unwrap();
+//! assert_eq!(session.state, Some(SessionState::AwaitingPlanApproval));
+//!
+//! client.approve_plan(&name).await?;
+//! let session = client.get_session(&name).await?;
+//! assert_eq!(session.state, Some(SessionState::Completed));
+//! assert_eq!(session.pull_requests().next().unwrap().number(), Some(7));
+//! assert_eq!(client.list_activities(&name, None, None).await?.activities.len(), 4);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::api::JulesApi;
+use crate::client::JulesClient;
+use crate::error::JulesError;
+use crate::models::*;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration as TimeDelta, Utc};
+use http::StatusCode;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// The API key accepted by a [`MockServer`] unless another is set.
+pub const MOCK_API_KEY: &str = "mock-api-key";
+
+/// An API call that [`Script`]s can react to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Event {
+    /// A session was created.
+    Create,
+    /// A message was sent to a session.
+    SendMessage,
+    /// A session's plan was approved.
+    ApprovePlan,
+}
+
+#[derive(Debug, Clone)]
+enum Step {
+    Activity(Box<Activity>),
+    State(SessionState),
+    Output(SessionOutput),
+}
+
+/// Changes applied to a session in response to an [`Event`].
+///
+/// Steps are applied in order. Activities get their name, id, and creation
+/// time from the server. Steps that end a session also set its state:
+/// [`plan`](Self::plan) moves it to `AWAITING_PLAN_APPROVAL` if the session
+/// requires plan approval and to `IN_PROGRESS` otherwise,
+/// [`complete`](Self::complete) to `COMPLETED`, and [`fail`](Self::fail) to
+/// `FAILED`.
+#[derive(Debug, Clone, Default)]
+pub struct Script {
+    steps: Vec<Step>,
+}
+
+impl Script {
+    /// Creates an empty script.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an activity. Its name, id, and creation time are replaced.
+    pub fn activity(mut self, activity: Activity) -> Self {
+        self.steps.push(Step::Activity(Box::new(activity)));
+        self
+    }
+
+    /// Adds an agent message.
+    pub fn agent_message(self, message: impl Into<String>) -> Self {
+        self.activity(Activity {
+            agent_messaged: Some(AgentMessaged {
+                agent_message: message.into(),
+            }),
+            ..blank_activity("agent")
+        })
+    }
+
+    /// Adds a generated plan with one step per title.
+    pub fn plan(self, steps: &[&str]) -> Self {
+        let steps = steps
+            .iter()
+            .enumerate()
+            .map(|(index, title)| PlanStep {
+                id: format!("step-{index}"),
+                title: title.to_string(),
+                description: String::new(),
+                index: index as i32,
+            })
+            .collect();
+        self.activity(Activity {
+            plan_generated: Some(PlanGenerated {
+                plan: Plan {
+                    id: String::new(),
+                    steps,
+                    create_time: Utc::now(),
+                },
+            }),
+            ..blank_activity("agent")
+        })
+    }
+
+    /// Adds a progress update.
+    pub fn progress(self, title: impl Into<String>) -> Self {
+        self.activity(Activity {
+            progress_updated: Some(ProgressUpdated {
+                title: title.into(),
+                description: String::new(),
+            }),
+            ..blank_activity("agent")
+        })
+    }
+
+    /// Adds a progress update with a command the agent ran.
+    pub fn bash(
+        self,
+        command: impl Into<String>,
+        output: impl Into<String>,
+        exit_code: i32,
+    ) -> Self {
+        self.activity(Activity {
+            progress_updated: Some(ProgressUpdated {
+                title: "Ran a command".to_string(),
+                description: String::new(),
+            }),
+            artifacts: Some(vec![Artifact {
+                change_set: None,
+                media: None,
+                bash_output: Some(BashOutput {
+                    command: command.into(),
+                    output: output.into(),
+                    exit_code,
+                }),
+            }]),
+            ..blank_activity("agent")
+        })
+    }
+
+    /// Adds a progress update with a code change on the session's source.
+    pub fn patch(
+        self,
+        unidiff_patch: impl Into<String>,
+        commit_message: impl Into<String>,
+    ) -> Self {
+        self.activity(Activity {
+            progress_updated: Some(ProgressUpdated {
+                title: "Updated code".to_string(),
+                description: String::new(),
+            }),
+            artifacts: Some(vec![Artifact {
+                change_set: Some(ChangeSet {
+                    git_patch: Some(GitPatch {
+                        unidiff_patch: unidiff_patch.into(),
+                        base_commit_id: "0000000000000000000000000000000000000000".to_string(),
+                        suggested_commit_message: Some(commit_message.into()),
+                    }),
+                    source: String::new(),
+                }),
+                media: None,
+                bash_output: None,
+            }]),
+            ..blank_activity("agent")
+        })
+    }
+
+    /// Adds a pull request to the session's outputs.
+    pub fn pull_request(self, url: impl Into<String>, title: impl Into<String>) -> Self {
+        self.output(SessionOutput {
+            pull_request: Some(PullRequest {
+                url: url.into(),
+                title: title.into(),
+                description: String::new(),
+            }),
+        })
+    }
+
+    /// Adds a session output.
+    pub fn output(mut self, output: SessionOutput) -> Self {
+        self.steps.push(Step::Output(output));
+        self
+    }
+
+    /// Sets the session state without adding an activity.
+    pub fn state(mut self, state: SessionState) -> Self {
+        self.steps.push(Step::State(state));
+        self
+    }
+
+    /// Adds a session completed activity and completes the session.
+    pub fn complete(self) -> Self {
+        self.activity(Activity {
+            session_completed: Some(json!({})),
+            ..blank_activity("system")
+        })
+        .state(SessionState::Completed)
+    }
+
+    /// Adds a session failed activity and fails the session.
+    pub fn fail(self, reason: impl Into<String>) -> Self {
+        self.activity(Activity {
+            session_failed: Some(SessionFailed {
+                reason: reason.into(),
+            }),
+            ..blank_activity("system")
+        })
+        .state(SessionState::Failed)
+    }
+}
+
+fn blank_activity(originator: &str) -> Activity {
+    Activity {
+        name: String::new(),
+        id: String::new(),
+        description: None,
+        create_time: Utc::now(),
+        originator: originator.to_string(),
+        agent_messaged: None,
+        user_messaged: None,
+        plan_generated: None,
+        plan_approved: None,
+        progress_updated: None,
+        session_completed: None,
+        session_failed: None,
+        artifacts: None,
+    }
+}
+
+/// A request received by a [`MockServer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedRequest {
+    /// The HTTP method, e.g. `GET`.
+    pub method: String,
+    /// The resource path, e.g. `sessions/1:approvePlan`.
+    pub path: String,
+    /// The decoded query parameters.
+    pub query: Vec<(String, String)>,
+    /// The request body.
+    pub body: String,
+}
+
+/// A failure a [`MockServer`] can produce instead of a normal response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fault {
+    /// Respond `429 Too Many Requests` with a `Retry-After` header.
+    RateLimited {
+        /// The delay sent in the `Retry-After` header, in whole seconds.
+        retry_after: Duration,
+    },
+    /// Respond with the given status code, e.g. `503`.
+    Status(u16),
+    /// Wait before handling the request normally.
+    Delay(Duration),
+    /// Handle the request, then send only the first half of the JSON body.
+    TruncatedJson,
+    /// Close the connection without responding.
+    Disconnect,
+    /// Respond `401 Unauthorized` as for an expired API key.
+    ExpiredAuth,
+    /// Handle a list request, then return the request's page token as the
+    /// next page token, so a client following tokens would loop forever.
+    PageTokenLoop,
+}
+
+/// When a [`MockServer`] should produce a [`Fault`].
+///
+/// By default a rule matches every request, indefinitely. Rules are checked
+/// in the order they were [injected](MockServer::inject); the first rule
+/// that matches a request applies to it.
+#[derive(Debug, Clone)]
+pub struct FaultRule {
+    fault: Fault,
+    method: Option<String>,
+    path: Option<String>,
+    skip: usize,
+    times: Option<usize>,
+}
+
+impl FaultRule {
+    /// Creates a rule that produces `fault` for every request.
+    pub fn new(fault: Fault) -> Self {
+        Self {
+            fault,
+            method: None,
+            path: None,
+            skip: 0,
+            times: None,
+        }
+    }
+
+    /// Only matches requests with this HTTP method, e.g. `POST`.
+    pub fn method(mut self, method: &str) -> Self {
+        self.method = Some(method.to_ascii_uppercase());
+        self
+    }
+
+    /// Only matches requests whose resource path starts with `prefix`, e.g.
+    /// `sessions/1/activities`.
+    pub fn path(mut self, prefix: impl Into<String>) -> Self {
+        self.path = Some(prefix.into());
+        self
+    }
+
+    /// Lets the first `n` matching requests through unchanged.
+    pub fn skip(mut self, n: usize) -> Self {
+        self.skip = n;
+        self
+    }
+
+    /// Produces the fault for `n` matching requests, then stops matching.
+    /// Several consecutive failures, such as a burst of `503`s, are
+    /// `FaultRule::new(Fault::Status(503)).times(3)`.
+    pub fn times(mut self, n: usize) -> Self {
+        self.times = Some(n);
+        self
+    }
+
+    fn matches(&self, request: &Request) -> bool {
+        self.method.as_ref().is_none_or(|m| *m == request.method)
+            && self
+                .path
+                .as_ref()
+                .is_none_or(|p| request.path.starts_with(p.as_str()))
+            && self.times != Some(0)
+    }
+}
+
+struct StoredSession {
+    session: Session,
+    activities: Vec<Activity>,
+}
+
+struct State {
+    api_key: String,
+    next_id: u64,
+    sessions: Vec<StoredSession>,
+    sources: Vec<Source>,
+    scripts: Vec<(Event, Script)>,
+    faults: Vec<FaultRule>,
+    requests: Vec<RecordedRequest>,
+}
+
+impl State {
+    fn new() -> Self {
+        Self {
+            api_key: MOCK_API_KEY.to_string(),
+            next_id: 1,
+            sessions: Vec::new(),
+            sources: Vec::new(),
+            scripts: Vec::new(),
+            faults: Vec::new(),
+            requests: Vec::new(),
+        }
+    }
+
+    fn insert_session(&mut self, mut session: Session) -> Session {
+        if session.name.is_none() {
+            let id = self.next_id.to_string();
+            self.next_id += 1;
+            session.name = Some(format!("sessions/{id}"));
+            session.id = Some(id);
+        }
+        let now = Utc::now();
+        session.create_time.get_or_insert(now);
+        session.update_time.get_or_insert(now);
+        session.state.get_or_insert(SessionState::Queued);
+        self.sessions.push(StoredSession {
+            session: session.clone(),
+            activities: Vec::new(),
+        });
+        session
+    }
+
+    /// Returns the fault for a request, consuming one use of its rule.
+    fn take_fault(&mut self, request: &Request) -> Option<Fault> {
+        for rule in &mut self.faults {
+            if !rule.matches(request) {
+                continue;
+            }
+            if rule.skip > 0 {
+                rule.skip -= 1;
+                continue;
+            }
+            if let Some(times) = &mut rule.times {
+                *times -= 1;
+            }
+            return Some(rule.fault.clone());
+        }
+        None
+    }
+
+    fn session_mut(&mut self, name: &str) -> Option<&mut StoredSession> {
+        self.sessions
+            .iter_mut()
+            .find(|s| s.session.name.as_deref() == Some(name))
+    }
+
+    fn run(&mut self, event: Event, session_name: &str) {
+        let scripts: Vec<Script> = self
+            .scripts
+            .iter()
+            .filter(|(e, _)| *e == event)
+            .map(|(_, s)| s.clone())
+            .collect();
+        for script in scripts {
+            self.apply(session_name, &script);
+        }
+    }
+
+    fn apply(&mut self, session_name: &str, script: &Script) {
+        let Some(stored) = self.session_mut(session_name) else {
+            return;
+        };
+        for step in &script.steps {
+            match step {
+                Step::Activity(activity) => {
+                    let mut activity = (**activity).clone();
+                    if let Some(plan) = &mut activity.plan_generated {
+                        plan.plan.id = format!("plan-{}", stored.activities.len() + 1);
+                        stored.session.state =
+                            Some(if stored.session.require_plan_approval == Some(true) {
+                                SessionState::AwaitingPlanApproval
+                            } else {
+                                SessionState::InProgress
+                            });
+                    }
+                    for artifact in activity.artifacts.iter_mut().flatten() {
+                        if let Some(change_set) = &mut artifact.change_set {
+                            if change_set.source.is_empty() {
+                                change_set.source = stored.session.source_context.source.clone();
+                            }
+                        }
+                    }
+                    stored.push_activity(activity);
+                }
+                Step::State(state) => stored.session.state = Some(state.clone()),
+                Step::Output(output) => stored
+                    .session
+                    .outputs
+                    .get_or_insert_with(Vec::new)
+                    .push(output.clone()),
+            }
+        }
+        stored.session.update_time = Some(Utc::now());
+    }
+}
+
+impl StoredSession {
+    fn push_activity(&mut self, mut activity: Activity) {
+        let name = self.session.name.as_deref().unwrap_or_default();
+        let id = (self.activities.len() + 1).to_string();
+        activity.name = format!("{name}/activities/{id}");
+        activity.id = id;
+        // Keep creation times strictly increasing, so ordering by time
+        // matches the order activities were added in.
+        let mut time = Utc::now();
+        if let Some(last) = self.activities.last() {
+            time = time.max(last.create_time + TimeDelta::microseconds(1));
+        }
+        activity.create_time = time;
+        self.activities.push(activity);
+        self.session.update_time = Some(time);
+    }
+}
+
+/// The sessions, sources, and scripts of a [`MockServer`] or [`FakeJules`].
+///
+/// Both dereference to `MockState`, so these methods are called directly on
+/// the server or fake.
+pub struct MockState {
+    state: Arc<Mutex<State>>,
+}
+
+impl MockState {
+    fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(State::new())),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        state_lock(&self.state)
+    }
+
+    /// Adds a source.
+    pub fn add_source(&self, source: Source) {
+        self.lock().sources.push(source);
+    }
+
+    /// Adds a GitHub source named `sources/github/{owner}/{repo}`.
+    ///
+    /// The first branch is the default branch.
+    pub fn add_github_source(&self, owner: &str, repo: &str, branches: &[&str]) -> Source {
+        let source = github_source(owner, repo, branches);
+        self.add_source(source.clone());
+        source
+    }
+
+    /// Stores a session as if it had been created, assigning a name and id
+    /// if it has none, and returns it.
+    pub fn insert_session(&self, session: Session) -> Session {
+        self.lock().insert_session(session)
+    }
+
+    /// Returns a session by name.
+    pub fn session(&self, name: &str) -> Option<Session> {
+        self.lock().session_mut(name).map(|s| s.session.clone())
+    }
+
+    /// Returns all sessions, oldest first.
+    pub fn sessions(&self) -> Vec<Session> {
+        self.lock()
+            .sessions
+            .iter()
+            .map(|s| s.session.clone())
+            .collect()
+    }
+
+    /// Returns the activities of a session, oldest first.
+    pub fn activities(&self, session_name: &str) -> Vec<Activity> {
+        self.lock()
+            .session_mut(session_name)
+            .map(|s| s.activities.clone())
+            .unwrap_or_default()
+    }
+
+    /// Runs `script` whenever `event` happens to any session.
+    ///
+    /// Scripts for the same event run in the order they were registered.
+    pub fn on(&self, event: Event, script: Script) {
+        self.lock().scripts.push((event, script));
+    }
+
+    /// Applies a script to a session now.
+    pub fn apply(&self, session_name: &str, script: &Script) {
+        self.lock().apply(session_name, script);
+    }
+}
+
+/// A local, in-memory Jules API server.
+///
+/// The server runs on a background thread until it is dropped. See the
+/// [module documentation](self) for an example.
+pub struct MockServer {
+    addr: SocketAddr,
+    state: MockState,
+    shutdown: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MockServer {
+    /// Starts a server on a free local port.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no local port can be bound.
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind the mock server");
+        let addr = listener.local_addr().expect("mock server has no address");
+        let state = MockState::new();
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let state = Arc::clone(&state.state);
+            let shutdown = Arc::clone(&shutdown);
+            std::thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if shutdown.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    let Ok(stream) = stream else { continue };
+                    let state = Arc::clone(&state);
+                    std::thread::spawn(move || handle_connection(stream, &state));
+                }
+            })
+        };
+        Self {
+            addr,
+            state,
+            shutdown,
+            thread: Some(thread),
+        }
+    }
+
+    /// Returns the base URL to pass to [`JulesClient::with_base_url`].
+    pub fn url(&self) -> String {
+        format!("http://{}/v1alpha/", self.addr)
+    }
+
+    /// Returns a client connected to this server.
+    pub fn client(&self) -> JulesClient {
+        let api_key = self.lock().api_key.clone();
+        JulesClient::with_base_url(api_key, &self.url()).expect("mock server URL is valid")
+    }
+
+    /// Sets the API key the server accepts (default [`MOCK_API_KEY`]).
+    ///
+    /// Requests with a different key are rejected with `401 Unauthorized`.
+    pub fn set_api_key(&self, api_key: impl Into<String>) {
+        self.lock().api_key = api_key.into();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock()
+    }
+
+    /// Adds a fault injection rule.
+    pub fn inject(&self, rule: FaultRule) {
+        self.lock().faults.push(rule);
+    }
+
+    /// Removes all fault injection rules.
+    pub fn clear_faults(&self) {
+        self.lock().faults.clear();
+    }
+
+    /// Returns every request received so far, oldest first.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.lock().requests.clone()
+    }
+}
+
+impl std::ops::Deref for MockServer {
+    type Target = MockState;
+
+    fn deref(&self) -> &MockState {
+        &self.state
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // Wake the accept loop so it sees the flag.
+        let _ = TcpStream::connect(self.addr);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// An in-memory implementation of [`JulesApi`] for unit tests.
+///
+/// `FakeJules` behaves like a [`MockServer`], including validation,
+/// pagination, and [`Script`]s, but answers calls directly instead of over
+/// HTTP. Errors are returned as [`JulesError::Api`] with the same status and
+/// body the server would send. Fault injection is only available on
+/// [`MockServer`].
+///
+/// # Example
+///
+/// ```rust
+/// use jules_rs::api::JulesApi;
+/// use jules_rs::testing::{Event, FakeJules, Script};
+/// use jules_rs::{Session, SessionState};
+///
+/// async fn approve_all(api: &dyn JulesApi) -> jules_rs::Result<()> {
+///     for session in api.list_sessions(None, None).await?.sessions {
+///         if session.state == Some(SessionState::AwaitingPlanApproval) {
+///             api.approve_plan(session.name.as_deref().unwrap_or_default()).await?;
+///         }
+///     }
+///     Ok(())
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() -> jules_rs::Result<()> {
+/// let fake = FakeJules::new();
+/// fake.add_github_source("acme", "widgets", &["main"]);
+/// fake.on(Event::ApprovePlan, Script::new().complete());
+/// let session = fake.insert_session(Session {
+///     state: Some(SessionState::AwaitingPlanApproval),
+///     ..serde_json::from_value(serde_json::json!({
+///         "prompt": "Fix the bug",
+///         "sourceContext": { "source": "sources/github/acme/widgets" },
+///     }))?
+/// });
+///
+/// approve_all(&fake).await?;
+/// let name = session.name.unwrap();
+/// assert_eq!(fake.session(&name).unwrap().state, Some(SessionState::Completed));
+/// # Ok(())
+/// # }
+/// ```
+pub struct FakeJules {
+    state: MockState,
+}
+
+impl Default for FakeJules {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FakeJules {
+    /// Creates a fake with no sessions or sources.
+    pub fn new() -> Self {
+        Self {
+            state: MockState::new(),
+        }
+    }
+
+    /// Answers a call the way the mock server would answer the HTTP request.
+    fn call<T: serde::de::DeserializeOwned>(
+        &self,
+        method: &str,
+        path: &str,
+        query: Vec<(&str, Option<String>)>,
+        body: Option<Value>,
+    ) -> crate::Result<T> {
+        let mut state = self.state.lock();
+        let request = Request {
+            method: method.to_string(),
+            path: path.to_string(),
+            query: query
+                .into_iter()
+                .filter_map(|(k, v)| Some((k.to_string(), v?)))
+                .collect(),
+            headers: HashMap::from([("x-goog-api-key".to_string(), state.api_key.clone())]),
+            body: body.map(|b| b.to_string()).unwrap_or_default(),
+        };
+        let response = route(&mut state, &request);
+        drop(state);
+        let status =
+            StatusCode::from_u16(response.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        if !status.is_success() {
+            return Err(JulesError::Api {
+                status,
+                message: response.body,
+            });
+        }
+        Ok(serde_json::from_str(&response.body)?)
+    }
+}
+
+impl std::ops::Deref for FakeJules {
+    type Target = MockState;
+
+    fn deref(&self) -> &MockState {
+        &self.state
+    }
+}
+
+#[async_trait]
+impl JulesApi for FakeJules {
+    async fn create_session(&self, session: &Session) -> crate::Result<Session> {
+        self.call("POST", "sessions", vec![], Some(json!(session)))
+    }
+
+    async fn get_session(&self, name: &str) -> crate::Result<Session> {
+        self.call("GET", name, vec![], None)
+    }
+
+    async fn delete_session(&self, name: &str) -> crate::Result<()> {
+        let _: Empty = self.call("DELETE", name, vec![], None)?;
+        Ok(())
+    }
+
+    async fn list_sessions(
+        &self,
+        page_size: Option<i32>,
+        page_token: Option<String>,
+    ) -> crate::Result<ListSessionsResponse> {
+        let query = vec![
+            ("pageSize", page_size.map(|n| n.to_string())),
+            ("pageToken", page_token),
+        ];
+        self.call("GET", "sessions", query, None)
+    }
+
+    async fn send_message(&self, session_name: &str, prompt: &str) -> crate::Result<()> {
+        let body = json!(SendMessageRequest {
+            prompt: prompt.to_string(),
+        });
+        let path = format!("{session_name}:sendMessage");
+        let _: Empty = self.call("POST", &path, vec![], Some(body))?;
+        Ok(())
+    }
+
+    async fn approve_plan(&self, session_name: &str) -> crate::Result<()> {
+        let path = format!("{session_name}:approvePlan");
+        let _: Empty = self.call("POST", &path, vec![], Some(json!({})))?;
+        Ok(())
+    }
+
+    async fn get_activity(&self, name: &str) -> crate::Result<Activity> {
+        self.call("GET", name, vec![], None)
+    }
+
+    async fn list_activities(
+        &self,
+        session_name: &str,
+        page_size: Option<i32>,
+        page_token: Option<String>,
+    ) -> crate::Result<ListActivitiesResponse> {
+        let query = vec![
+            ("pageSize", page_size.map(|n| n.to_string())),
+            ("pageToken", page_token),
+        ];
+        self.call("GET", &format!("{session_name}/activities"), query, None)
+    }
+
+    async fn get_source(&self, name: &str) -> crate::Result<Source> {
+        self.call("GET", name, vec![], None)
+    }
+
+    async fn list_sources(
+        &self,
+        filter: Option<String>,
+        page_size: Option<i32>,
+        page_token: Option<String>,
+    ) -> crate::Result<ListSourcesResponse> {
+        let query = vec![
+            ("filter", filter),
+            ("pageSize", page_size.map(|n| n.to_string())),
+            ("pageToken", page_token),
+        ];
+        self.call("GET", "sources", query, None)
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    headers: HashMap<String, String>,
+    body: String,
+}
+
+impl Request {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+struct Response {
+    status: u16,
+    headers: Vec<(&'static str, String)>,
+    body: String,
+}
+
+impl Response {
+    fn json(value: Value) -> Self {
+        Self {
+            status: 200,
+            headers: Vec::new(),
+            body: value.to_string(),
+        }
+    }
+
+    fn error(status: u16, code: &str, message: impl Into<String>) -> Self {
+        let body = json!({
+            "error": { "code": status, "message": message.into(), "status": code }
+        });
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.to_string(),
+        }
+    }
+
+    fn not_found(what: &str) -> Self {
+        Self::error(404, "NOT_FOUND", format!("{what} not found"))
+    }
+}
+
+fn handle_connection(stream: TcpStream, state_ref: &Mutex<State>) {
+    let mut reader = BufReader::new(&stream);
+    let request = match read_request(&mut reader) {
+        Some(Ok(request)) => request,
+        Some(Err(response)) => return write_response(&stream, &response),
+        None => return,
+    };
+    let mut state = state_lock(state_ref);
+    state.requests.push(RecordedRequest {
+        method: request.method.clone(),
+        path: request.path.clone(),
+        query: request.query.clone(),
+        body: request.body.clone(),
+    });
+    let fault = state.take_fault(&request);
+    let response = match &fault {
+        Some(Fault::RateLimited { retry_after }) => {
+            let mut response =
+                Response::error(429, "RESOURCE_EXHAUSTED", "Quota exceeded; retry later");
+            response
+                .headers
+                .push(("Retry-After", retry_after.as_secs().to_string()));
+            response
+        }
+        Some(Fault::Status(status)) => {
+            Response::error(*status, "UNAVAILABLE", "Injected server error")
+        }
+        Some(Fault::ExpiredAuth) => Response::error(
+            401,
+            "UNAUTHENTICATED",
+            "API key expired. Please renew the API key.",
+        ),
+        Some(Fault::Disconnect) => return,
+        Some(Fault::Delay(delay)) => {
+            // Release the state while sleeping so other requests proceed.
+            let delay = *delay;
+            drop(state);
+            std::thread::sleep(delay);
+            state = state_lock(state_ref);
+            route(&mut state, &request)
+        }
+        Some(Fault::TruncatedJson | Fault::PageTokenLoop) | None => route(&mut state, &request),
+    };
+    drop(state);
+
+    let mut response = response;
+    match fault {
+        Some(Fault::TruncatedJson) => {
+            let mut end = response.body.len() / 2;
+            while !response.body.is_char_boundary(end) {
+                end -= 1;
+            }
+            response.body.truncate(end);
+        }
+        Some(Fault::PageTokenLoop) if response.status == 200 => {
+            if let Some(token) = request.param("pageToken").filter(|t| !t.is_empty()) {
+                if let Ok(mut body) = serde_json::from_str::<Value>(&response.body) {
+                    body["nextPageToken"] = json!(token);
+                    response.body = body.to_string();
+                }
+            }
+        }
+        _ => {}
+    }
+    write_response(&stream, &response);
+}
+
+fn state_lock(state: &Mutex<State>) -> MutexGuard<'_, State> {
+    state.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// The largest request body the mock server accepts.
+const MAX_BODY_LEN: usize = 16 * 1024 * 1024;
+
+/// Reads one request. Returns `None` if the connection closed or the request
+/// is malformed, and an error response if the body is too large to read.
+fn read_request(reader: &mut impl BufRead) -> Option<std::result::Result<Request, Response>> {
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let target = parts.next()?;
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).ok()?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+    let length: usize = headers
+        .get("content-length")
+        .and_then(|l| l.parse().ok())
+        .unwrap_or(0);
+    if length > MAX_BODY_LEN {
+        return Some(Err(Response::error(
+            413,
+            "INVALID_ARGUMENT",
+            format!("request body of {length} bytes exceeds {MAX_BODY_LEN} bytes"),
+        )));
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).ok()?;
+
+    let url = url::Url::parse(&format!("http://localhost{target}")).ok()?;
+    let path = url.path().strip_prefix("/v1alpha/").unwrap_or(url.path());
+    Some(Ok(Request {
+        method,
+        path: path.to_string(),
+        query: url.query_pairs().into_owned().collect(),
+        headers,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    }))
+}
+
+fn write_response(mut stream: &TcpStream, response: &Response) {
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        413 => "Content Too Large",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        _ => "Error",
+    };
+    let mut head = format!(
+        "HTTP/1.1 {} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        response.body.len()
+    );
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+    head.push_str("\r\n");
+    let _ = stream.write_all(head.as_bytes());
+    let _ = stream.write_all(response.body.as_bytes());
+    let _ = stream.flush();
+}
+
+fn route(state: &mut State, request: &Request) -> Response {
+    if request.headers.get("x-goog-api-key") != Some(&state.api_key) {
+        return Response::error(401, "UNAUTHENTICATED", "API key not valid");
+    }
+    let segments: Vec<&str> = request.path.split('/').collect();
+    match (request.method.as_str(), segments.as_slice()) {
+        ("POST", ["sessions"]) => create_session(state, request),
+        ("GET", ["sessions"]) => list_sessions(state, request),
+        ("POST", ["sessions", action]) if action.contains(':') => {
+            let (id, action) = action.split_once(':').unwrap_or_default();
+            let name = format!("sessions/{id}");
+            match action {
+                "sendMessage" => send_message(state, &name, request),
+                "approvePlan" => approve_plan(state, &name),
+                _ => Response::error(404, "NOT_FOUND", format!("unknown method `{action}`")),
+            }
+        }
+        ("GET", ["sessions", _]) => match state.session_mut(&request.path) {
+            Some(stored) => session_json(&stored.session),
+            None => Response::not_found("session"),
+        },
+        ("DELETE", ["sessions", _]) => {
+            let before = state.sessions.len();
+            state
+                .sessions
+                .retain(|s| s.session.name.as_deref() != Some(request.path.as_str()));
+            if state.sessions.len() == before {
+                Response::not_found("session")
+            } else {
+                Response::json(json!({}))
+            }
+        }
+        ("GET", ["sessions", id, "activities"]) => {
+            let name = format!("sessions/{id}");
+            match state.session_mut(&name) {
+                Some(stored) => {
+                    let page = paginate(&stored.activities, request, 50);
+                    page.map_or_else(|e| e, |(items, next)| list_json("activities", items, next))
+                }
+                None => Response::not_found("session"),
+            }
+        }
+        ("GET", ["sessions", id, "activities", _]) => {
+            let name = format!("sessions/{id}");
+            let activity = state
+                .session_mut(&name)
+                .and_then(|s| s.activities.iter().find(|a| a.name == request.path));
+            match activity {
+                Some(activity) => Response::json(json!(activity)),
+                None => Response::not_found("activity"),
+            }
+        }
+        ("GET", ["sources"]) => {
+            let names = match request.param("filter").map(parse_source_filter) {
+                Some(Ok(names)) => Some(names),
+                Some(Err(message)) => return Response::error(400, "INVALID_ARGUMENT", message),
+                None => None,
+            };
+            let sources: Vec<Source> = state
+                .sources
+                .iter()
+                .filter(|s| names.as_ref().is_none_or(|n| n.contains(&s.name)))
+                .cloned()
+                .collect();
+            paginate(&sources, request, 30)
+                .map_or_else(|e| e, |(items, next)| list_json("sources", items, next))
+        }
+        ("GET", ["sources", ..]) => match state.sources.iter().find(|s| s.name == request.path) {
+            Some(source) => Response::json(json!(source)),
+            None => Response::not_found("source"),
+        },
+        _ => Response::error(
+            404,
+            "NOT_FOUND",
+            format!("no route for {} {}", request.method, request.path),
+        ),
+    }
+}
+
+fn session_json(session: &Session) -> Response {
+    match session.to_json_value() {
+        Ok(value) => Response::json(value),
+        Err(e) => Response::error(500, "INTERNAL", e.to_string()),
+    }
+}
+
+fn list_json<T: serde::Serialize>(field: &str, items: Vec<T>, next: Option<String>) -> Response {
+    let mut body = json!({ field: items });
+    if let Some(next) = next {
+        body["nextPageToken"] = json!(next);
+    }
+    Response::json(body)
+}
+
+/// Returns one page of `items` and the token for the next page.
+fn paginate<T: Clone>(
+    items: &[T],
+    request: &Request,
+    default_size: usize,
+) -> Result<(Vec<T>, Option<String>), Response> {
+    let size = match request.param("pageSize").map(str::parse::<i64>) {
+        None | Some(Ok(0)) => default_size,
+        Some(Ok(n)) if n > 0 => (n as usize).min(100),
+        _ => {
+            return Err(Response::error(
+                400,
+                "INVALID_ARGUMENT",
+                "invalid page size",
+            ));
+        }
+    };
+    let start = match request.param("pageToken").filter(|t| !t.is_empty()) {
+        None => 0,
+        Some(token) => match token.parse::<usize>() {
+            Ok(start) if start <= items.len() => start,
+            _ => {
+                return Err(Response::error(
+                    400,
+                    "INVALID_ARGUMENT",
+                    "invalid page token",
+                ));
+            }
+        },
+    };
+    let end = (start + size).min(items.len());
+    let next = (end < items.len()).then(|| end.to_string());
+    Ok((items[start..end].to_vec(), next))
+}
+
+fn github_source(owner: &str, repo: &str, branches: &[&str]) -> Source {
+    let branches: Vec<GitHubBranch> = branches
+        .iter()
+        .map(|b| GitHubBranch {
+            display_name: b.to_string(),
+        })
+        .collect();
+    Source {
+        name: format!("sources/github/{owner}/{repo}"),
+        id: format!("github/{owner}/{repo}"),
+        github_repo: Some(GitHubRepo {
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+            is_private: false,
+            default_branch: branches.first().cloned().unwrap_or(GitHubBranch {
+                display_name: "main".to_string(),
+            }),
+            branches,
+        }),
+    }
+}
+
+/// Parses `name=sources/a OR name=sources/b`.
+fn parse_source_filter(filter: &str) -> Result<Vec<String>, String> {
+    filter
+        .split(" OR ")
+        .map(|term| {
+            term.trim()
+                .strip_prefix("name=")
+                .map(|name| name.trim().trim_matches('"').to_string())
+                .ok_or_else(|| format!("unsupported filter `{filter}`"))
+        })
+        .collect()
+}
+
+fn create_session(state: &mut State, request: &Request) -> Response {
+    let mut session: Session = match serde_json::from_str(&request.body) {
+        Ok(session) => session,
+        Err(e) => return Response::error(400, "INVALID_ARGUMENT", e.to_string()),
+    };
+    if session.prompt.trim().is_empty() {
+        return Response::error(400, "INVALID_ARGUMENT", "prompt is required");
+    }
+    let source = &session.source_context.source;
+    let Some(known) = state.sources.iter().find(|s| &s.name == source) else {
+        return Response::not_found("source");
+    };
+    if let (Some(gh), Some(ctx)) = (
+        &known.github_repo,
+        &session.source_context.github_repo_context,
+    ) {
+        if !gh.has_branch(&ctx.starting_branch) {
+            return Response::error(
+                400,
+                "INVALID_ARGUMENT",
+                format!("branch `{}` not found", ctx.starting_branch),
+            );
+        }
+    }
+
+    let id = state.next_id.to_string();
+    state.next_id += 1;
+    let now: DateTime<Utc> = Utc::now();
+    let name = format!("sessions/{id}");
+    session.name = Some(name.clone());
+    session.url = Some(format!("https://jules.google.com/session/{id}"));
+    session.id = Some(id);
+    session.create_time = Some(now);
+    session.update_time = Some(now);
+    session.state = Some(SessionState::Queued);
+    session.outputs = None;
+    state.sessions.push(StoredSession {
+        session,
+        activities: Vec::new(),
+    });
+    state.run(Event::Create, &name);
+    match state.session_mut(&name) {
+        Some(stored) => session_json(&stored.session),
+        None => Response::not_found("session"),
+    }
+}
+
+fn list_sessions(state: &State, request: &Request) -> Response {
+    // Newest first, like the real API.
+    let sessions: Vec<&Session> = state.sessions.iter().rev().map(|s| &s.session).collect();
+    match paginate(&sessions, request, 30) {
+        Ok((page, next)) => {
+            let page: Result<Vec<Value>, _> =
+                page.into_iter().map(Session::to_json_value).collect();
+            match page {
+                Ok(page) => list_json("sessions", page, next),
+                Err(e) => Response::error(500, "INTERNAL", e.to_string()),
+            }
+        }
+        Err(response) => response,
+    }
+}
+
+fn send_message(state: &mut State, name: &str, request: &Request) -> Response {
+    let message: SendMessageRequest = match serde_json::from_str(&request.body) {
+        Ok(message) => message,
+        Err(e) => return Response::error(400, "INVALID_ARGUMENT", e.to_string()),
+    };
+    let Some(stored) = state.session_mut(name) else {
+        return Response::not_found("session");
+    };
+    stored.push_activity(Activity {
+        user_messaged: Some(UserMessaged {
+            user_message: message.prompt,
+        }),
+        ..blank_activity("user")
+    });
+    state.run(Event::SendMessage, name);
+    Response::json(json!({}))
+}
+
+fn approve_plan(state: &mut State, name: &str) -> Response {
+    let Some(stored) = state.session_mut(name) else {
+        return Response::not_found("session");
+    };
+    if stored.session.state != Some(SessionState::AwaitingPlanApproval) {
+        return Response::error(
+            400,
+            "FAILED_PRECONDITION",
+            "session is not awaiting plan approval",
+        );
+    }
+    let plan_id = stored
+        .activities
+        .iter()
+        .rev()
+        .find_map(|a| a.plan_generated.as_ref())
+        .map(|p| p.plan.id.clone())
+        .unwrap_or_default();
+    stored.push_activity(Activity {
+        plan_approved: Some(PlanApproved { plan_id }),
+        ..blank_activity("user")
+    });
+    stored.session.state = Some(SessionState::InProgress);
+    state.run(Event::ApprovePlan, name);
+    Response::json(json!({}))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+    use std::io::Read;
+    use std::time::Instant;
+
+    fn session(prompt: &str) -> Session {
+        Session {
+            name: None,
+            id: None,
+            prompt: prompt.to_string(),
+            source_context: SourceContext {
+                source: "sources/github/acme/widgets".to_string(),
+                github_repo_context: None,
+            },
+            title: None,
+            require_plan_approval: None,
+            automation_mode: None,
+            create_time: None,
+            update_time: None,
+            state: None,
+            url: None,
+            outputs: None,
+        }
+    }
+
+    fn api_status<T: std::fmt::Debug>(result: crate::Result<T>) -> u16 {
+        match result {
+            Err(JulesError::Api { status, .. }) => status.as_u16(),
+            other => panic!("expected an API error, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn rate_limited_is_too_many_requests() {
+        let server = MockServer::start();
+        server.inject(
+            FaultRule::new(Fault::RateLimited {
+                retry_after: Duration::from_secs(7),
+            })
+            .times(1),
+        );
+        let client = server.client();
+        assert_eq!(api_status(client.list_sessions(None, None).await), 429);
+        assert!(client.list_sessions(None, None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn status_fails_the_stream() {
+        let server = MockServer::start();
+        server.insert_session(session("a"));
+        server.inject(FaultRule::new(Fault::Status(503)).times(1));
+        let client = server.client();
+        let items: Vec<_> = client.stream_sessions().collect().await;
+        assert_eq!(items.len(), 1);
+        assert_eq!(api_status(items.into_iter().next().unwrap()), 503);
+    }
+
+    #[tokio::test]
+    async fn expired_auth_is_unauthorized() {
+        let server = MockServer::start();
+        server.inject(FaultRule::new(Fault::ExpiredAuth).path("sources"));
+        let client = server.client();
+        assert_eq!(api_status(client.list_sources(None, None, None).await), 401);
+        assert!(client.list_sessions(None, None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn disconnect_is_an_http_error() {
+        let server = MockServer::start();
+        server.inject(FaultRule::new(Fault::Disconnect).times(1));
+        let client = server.client();
+        assert!(matches!(
+            client.list_sessions(None, None).await,
+            Err(JulesError::Http(_))
+        ));
+        assert!(client.list_sessions(None, None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn delay_still_answers() {
+        let server = MockServer::start();
+        let delay = Duration::from_millis(200);
+        server.inject(FaultRule::new(Fault::Delay(delay)).times(1));
+        let client = server.client();
+        let start = Instant::now();
+        assert!(client.list_sessions(None, None).await.is_ok());
+        assert!(start.elapsed() >= delay);
+    }
+
+    #[tokio::test]
+    async fn truncated_json_is_a_serialization_error() {
+        let server = MockServer::start();
+        server.insert_session(session("a"));
+        server.inject(FaultRule::new(Fault::TruncatedJson).method("GET").times(1));
+        let client = server.client();
+        assert!(matches!(
+            client.list_sessions(None, None).await,
+            Err(JulesError::Serialization(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn page_token_loop_ends_the_stream() {
+        let server = MockServer::start();
+        for i in 0..101 {
+            server.insert_session(session(&i.to_string()));
+        }
+        server.inject(FaultRule::new(Fault::PageTokenLoop).path("sessions"));
+        let client = server.client();
+        let items: Vec<_> = client.stream_sessions().collect().await;
+        assert_eq!(items.len(), 102);
+        assert!(items[..101].iter().all(Result::is_ok));
+        assert!(matches!(
+            items.last(),
+            Some(Err(JulesError::PageTokenLoop(token))) if token == "100"
+        ));
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[test]
+    fn oversized_bodies_are_rejected() {
+        let server = MockServer::start();
+        let mut stream = TcpStream::connect(server.addr).unwrap();
+        write!(
+            stream,
+            "POST /v1alpha/sessions HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            MAX_BODY_LEN + 1
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 413 "), "{response}");
+    }
+}
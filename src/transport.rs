@@ -0,0 +1,448 @@
+//! Pluggable HTTP transport and timers.
+//!
+//! A [`JulesClient`] sends every request through an [`HttpTransport`] once it
+//! has passed through the client's middleware. Requests and responses are
+//! [`http`] types with the whole body in memory, re-exported here, so a
+//! transport can be written for any HTTP library. With the default `reqwest`
+//! feature, [`ReqwestTransport`] is used unless
+//! [`JulesClient::with_transport`] sets another; without it, a transport must
+//! be set before making requests.
+//!
+//! Polling helpers such as [`watch_session`](JulesClient::watch_session) and
+//! the [`Orchestrator`](crate::orchestrator::Orchestrator) wait between polls
+//! with the client's [`Sleep`]. With the `tokio-runtime` feature this is
+//! [`TokioSleep`]. Without it there is no default: set your runtime's timer
+//! with [`JulesClient::with_sleep`], or the polling helpers fail with
+//! [`JulesError::NoSleep`].
+//!
+//! # Example
+//!
+//! A transport that answers every request in memory:
+//!
+//! ```rust
+//! use async_trait::async_trait;
+//! use jules_rs::JulesClient;
+//! use jules_rs::transport::{HttpTransport, Request, Response};
+//!
+//! /// Answers every request with an empty list of sessions.
+//! struct NoSessions;
+//!
+//! #[async_trait]
+//! impl HttpTransport for NoSessions {
+//!     async fn send(&self, _request: Request) -> jules_rs::Result<Response> {
+//!         Ok(Response::new(br#"{"sessions": []}"#.to_vec()))
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> jules_rs::Result<()> {
+//! let client = JulesClient::new("YOUR_API_KEY")?.with_transport(NoSessions);
+//! assert!(client.list_sessions(None, None).await?.sessions.is_empty());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::JulesClient;
+use crate::error::{JulesError, Result};
+use async_trait::async_trait;
+use http::header::{CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue};
+use http::{Method, Uri};
+use serde::Serialize;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+use url::Url;
+
+pub use http;
+
+/// A request sent by a [`JulesClient`], with its body.
+pub type Request = http::Request<Vec<u8>>;
+
+/// A response to a [`Request`], with its body.
+pub type Response = http::Response<Vec<u8>>;
+
+/// Sends HTTP requests for a [`JulesClient`].
+#[async_trait]
+pub trait HttpTransport: Send + Sync + 'static {
+    /// Sends a request and returns the response, whatever its status.
+    ///
+    /// Only failures to get a response at all should be returned as errors.
+    async fn send(&self, request: Request) -> Result<Response>;
+}
+
+#[async_trait]
+impl<T: HttpTransport + ?Sized> HttpTransport for Arc<T> {
+    async fn send(&self, request: Request) -> Result<Response> {
+        (**self).send(request).await
+    }
+}
+
+/// The default transport, which sends requests with a [`reqwest::Client`].
+///
+/// Requires the `reqwest` feature (enabled by default).
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use jules_rs::JulesClient;
+/// use jules_rs::transport::ReqwestTransport;
+///
+/// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let http = reqwest::Client::builder()
+///     .timeout(Duration::from_secs(30))
+///     .build()?;
+/// let client = JulesClient::new("YOUR_API_KEY")?.with_transport(ReqwestTransport::from(http));
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "reqwest")]
+#[cfg_attr(docsrs, doc(cfg(feature = "reqwest")))]
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+#[cfg(feature = "reqwest")]
+impl ReqwestTransport {
+    /// Creates a transport with a default `reqwest` client.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl From<reqwest::Client> for ReqwestTransport {
+    /// Uses a configured `reqwest` client, e.g. one with a proxy or custom
+    /// timeouts.
+    fn from(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+#[cfg(feature = "reqwest")]
+#[async_trait]
+impl HttpTransport for ReqwestTransport {
+    async fn send(&self, request: Request) -> Result<Response> {
+        let response = self
+            .client
+            .execute(reqwest::Request::try_from(request)?)
+            .await?;
+        let status = response.status();
+        let version = response.version();
+        let headers = response.headers().clone();
+        // The body of an error response is read on a best-effort basis.
+        let body = match response.bytes().await {
+            Ok(body) => body.to_vec(),
+            Err(_) if !status.is_success() => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let mut response = Response::new(body);
+        *response.status_mut() = status;
+        *response.version_mut() = version;
+        *response.headers_mut() = headers;
+        Ok(response)
+    }
+}
+
+/// The transport used without the `reqwest` feature until
+/// [`JulesClient::with_transport`] is called.
+#[cfg(not(feature = "reqwest"))]
+struct NoTransport;
+
+#[cfg(not(feature = "reqwest"))]
+#[async_trait]
+impl HttpTransport for NoTransport {
+    async fn send(&self, _request: Request) -> Result<Response> {
+        Err(JulesError::NoTransport)
+    }
+}
+
+/// The transport used unless [`JulesClient::with_transport`] is called.
+pub(crate) fn default_transport() -> Arc<dyn HttpTransport> {
+    #[cfg(feature = "reqwest")]
+    return Arc::new(ReqwestTransport::new());
+    #[cfg(not(feature = "reqwest"))]
+    return Arc::new(NoTransport);
+}
+
+/// Builds a [`Request`], keeping the first error until
+/// [`build`](Self::build).
+pub(crate) struct RequestBuilder {
+    method: Method,
+    url: Url,
+    headers: HeaderMap,
+    body: Vec<u8>,
+    error: Option<JulesError>,
+}
+
+impl RequestBuilder {
+    pub(crate) fn new(method: Method, url: Url) -> Self {
+        Self {
+            method,
+            url,
+            headers: HeaderMap::new(),
+            body: Vec::new(),
+            error: None,
+        }
+    }
+
+    pub(crate) fn header(self, name: &'static str, value: &str) -> Self {
+        self.insert_header(name, value, false)
+    }
+
+    /// Adds a header that holds a credential. It is marked sensitive, so
+    /// HTTP libraries that honour the flag keep it out of logs.
+    pub(crate) fn sensitive_header(self, name: &'static str, value: &str) -> Self {
+        self.insert_header(name, value, true)
+    }
+
+    fn insert_header(mut self, name: &'static str, value: &str, sensitive: bool) -> Self {
+        match HeaderValue::from_str(value) {
+            Ok(mut value) => {
+                value.set_sensitive(sensitive);
+                self.headers.insert(HeaderName::from_static(name), value);
+            }
+            Err(e) => {
+                self.error
+                    .get_or_insert_with(|| http::Error::from(e).into());
+            }
+        }
+        self
+    }
+
+    pub(crate) fn query(mut self, name: &str, value: impl ToString) -> Self {
+        self.url
+            .query_pairs_mut()
+            .append_pair(name, &value.to_string());
+        self
+    }
+
+    pub(crate) fn json(mut self, body: &impl Serialize) -> Self {
+        match serde_json::to_vec(body) {
+            Ok(body) => {
+                self.body = body;
+                self.headers
+                    .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+            }
+            Err(e) => {
+                self.error.get_or_insert(e.into());
+            }
+        }
+        self
+    }
+
+    pub(crate) fn build(self) -> Result<Request> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        let uri: Uri = self.url.as_str().parse().map_err(http::Error::from)?;
+        let mut request = Request::new(self.body);
+        *request.method_mut() = self.method;
+        *request.uri_mut() = uri;
+        *request.headers_mut() = self.headers;
+        Ok(request)
+    }
+}
+
+/// Waits between polls in the polling helpers.
+#[async_trait]
+pub trait Sleep: Send + Sync + 'static {
+    /// Completes after `duration` has passed.
+    async fn sleep(&self, duration: Duration);
+}
+
+#[async_trait]
+impl<T: Sleep + ?Sized> Sleep for Arc<T> {
+    async fn sleep(&self, duration: Duration) {
+        (**self).sleep(duration).await;
+    }
+}
+
+/// Sleeps with [`tokio::time::sleep`]. Requires a tokio runtime with the
+/// timer enabled.
+///
+/// Requires the `tokio-runtime` feature.
+#[cfg(feature = "tokio-runtime")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio-runtime")))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokioSleep;
+
+#[cfg(feature = "tokio-runtime")]
+#[async_trait]
+impl Sleep for TokioSleep {
+    async fn sleep(&self, duration: Duration) {
+        tokio::time::sleep(duration).await;
+    }
+}
+
+/// Sleeps on a background thread, so it works with any executor.
+///
+/// Each sleep spawns an OS thread that lives for the whole duration, so a
+/// client watching many sessions holds one thread per watcher between
+/// polls. Prefer your runtime's timer; this is a fallback for executors
+/// without one.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ThreadSleep;
+
+#[async_trait]
+impl Sleep for ThreadSleep {
+    async fn sleep(&self, duration: Duration) {
+        ThreadTimer {
+            duration,
+            shared: None,
+        }
+        .await;
+    }
+}
+
+struct ThreadTimer {
+    duration: Duration,
+    shared: Option<Arc<Mutex<TimerState>>>,
+}
+
+#[derive(Default)]
+struct TimerState {
+    done: bool,
+    waker: Option<Waker>,
+}
+
+impl Future for ThreadTimer {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.duration.is_zero() {
+            return Poll::Ready(());
+        }
+        let duration = self.duration;
+        let shared = self.shared.get_or_insert_with(|| {
+            let shared = Arc::new(Mutex::new(TimerState::default()));
+            let timer = Arc::clone(&shared);
+            std::thread::spawn(move || {
+                std::thread::sleep(duration);
+                let mut state = timer.lock().unwrap_or_else(|e| e.into_inner());
+                state.done = true;
+                if let Some(waker) = state.waker.take() {
+                    waker.wake();
+                }
+            });
+            shared
+        });
+        let mut state = shared.lock().unwrap_or_else(|e| e.into_inner());
+        if state.done {
+            Poll::Ready(())
+        } else {
+            state.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+/// The sleep used unless [`JulesClient::with_sleep`] is called.
+pub(crate) fn default_sleep() -> Option<Arc<dyn Sleep>> {
+    #[cfg(feature = "tokio-runtime")]
+    return Some(Arc::new(TokioSleep));
+    #[cfg(not(feature = "tokio-runtime"))]
+    return None;
+}
+
+impl JulesClient {
+    /// Sends requests with `transport` instead of the default transport.
+    ///
+    /// Middleware still runs before the transport. Replaces any transport
+    /// set before, including a `with_layer` stack, so call this first when
+    /// combining them. GitHub lookups made by
+    /// [`continue_from`](JulesClient::continue_from) use `transport` too,
+    /// without the client's middleware or layers.
+    pub fn with_transport(mut self, transport: impl HttpTransport) -> Self {
+        let transport: Arc<dyn HttpTransport> = Arc::new(transport);
+        self.github = Arc::clone(&transport);
+        self.transport = transport;
+        self
+    }
+
+    /// Waits between polls with `sleep`; see the
+    /// [module documentation](crate::transport).
+    pub fn with_sleep(mut self, sleep: impl Sleep) -> Self {
+        self.sleep = Some(Arc::new(sleep));
+        self
+    }
+
+    /// The client's [`Sleep`], for the polling helpers.
+    pub(crate) fn sleeper(&self) -> Result<&dyn Sleep> {
+        self.sleep.as_deref().ok_or(JulesError::NoSleep)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::time::Instant;
+
+    fn url() -> Url {
+        Url::parse("https://example.com/v1alpha/sessions").unwrap()
+    }
+
+    #[test]
+    fn zero_duration_timers_are_ready_at_once() {
+        let mut timer = ThreadTimer {
+            duration: Duration::ZERO,
+            shared: None,
+        };
+        let mut cx = Context::from_waker(Waker::noop());
+        assert_eq!(Pin::new(&mut timer).poll(&mut cx), Poll::Ready(()));
+        assert!(timer.shared.is_none(), "no thread should be spawned");
+    }
+
+    #[tokio::test]
+    async fn thread_sleep_wakes_after_the_duration() {
+        let duration = Duration::from_millis(30);
+        let start = Instant::now();
+        ThreadSleep.sleep(duration).await;
+        assert!(start.elapsed() >= duration);
+    }
+
+    #[test]
+    fn builds_requests() {
+        let request = RequestBuilder::new(Method::POST, url())
+            .header("accept", "application/json")
+            .sensitive_header("x-goog-api-key", "key")
+            .query("pageSize", 5)
+            .json(&serde_json::json!({ "prompt": "p" }))
+            .build()
+            .unwrap();
+        assert_eq!(request.method(), Method::POST);
+        assert_eq!(
+            request.uri(),
+            "https://example.com/v1alpha/sessions?pageSize=5"
+        );
+        assert!(request.headers()["x-goog-api-key"].is_sensitive());
+        assert!(!request.headers()["accept"].is_sensitive());
+        assert_eq!(request.headers()[CONTENT_TYPE], "application/json");
+        assert_eq!(request.body(), br#"{"prompt":"p"}"#);
+    }
+
+    #[test]
+    fn keeps_the_first_error_until_build() {
+        // JSON object keys must be strings.
+        let unserializable = HashMap::from([((1, 2), "value")]);
+        let result = RequestBuilder::new(Method::GET, url())
+            .header("accept", "bad\nvalue")
+            .json(&unserializable)
+            .header("user-agent", "also\rbad")
+            .build();
+        assert!(matches!(result, Err(JulesError::Http(_))), "{result:?}");
+
+        let result = RequestBuilder::new(Method::GET, url())
+            .json(&unserializable)
+            .header("accept", "bad\nvalue")
+            .build();
+        assert!(
+            matches!(result, Err(JulesError::Serialization(_))),
+            "{result:?}"
+        );
+    }
+}
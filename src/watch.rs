@@ -0,0 +1,276 @@
+//! Following sessions as they progress.
+//!
+//! The Jules API has no push notifications, so [`JulesClient::watch_session`]
+//! polls a session and its activities and yields each new activity and state
+//! change once, in order. The stream ends after the session completes or
+//! fails. Between polls it waits with the client's
+//! [`Sleep`](crate::transport::Sleep); without the `tokio-runtime` feature,
+//! set one with [`JulesClient::with_sleep`].
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use futures_util::StreamExt;
+//! use jules_rs::JulesClient;
+//! use jules_rs::watch::WatchEvent;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = JulesClient::new("TOKEN")?;
+//! let mut events = client.watch_session("sessions/abc123", Duration::from_secs(5));
+//!
+//! while let Some(event) = events.next().await {
+//!     match event? {
+//!         WatchEvent::Activity(activity) => println!("{}", activity.id),
+//!         WatchEvent::StateChanged { to, .. } => println!("now {}", to.as_str()),
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::client::JulesClient;
+use crate::error::Result;
+use crate::models::{Activity, SessionState};
+use futures_util::{StreamExt, stream::Stream};
+use std::collections::{HashSet, VecDeque};
+use std::pin::Pin;
+use std::time::Duration;
+
+/// An event observed while watching a session.
+#[derive(Debug, Clone)]
+pub enum WatchEvent {
+    /// A new activity was added to the session.
+    Activity(Box<Activity>),
+    /// The session moved to a different state.
+    StateChanged {
+        /// The previous state, or `None` on the first observation.
+        from: Option<SessionState>,
+        /// The new state.
+        to: SessionState,
+    },
+}
+
+impl JulesClient {
+    /// Follows a session, yielding new activities and state changes.
+    ///
+    /// The session is polled every `poll_interval`. The first poll reports
+    /// the current state and all existing activities. Activities are
+    /// reported in creation order and never twice. The stream ends once the
+    /// session is [terminal](SessionState::is_terminal) and its final
+    /// activities have been yielded, or after the first error. If it needs
+    /// to wait and the client has no [`Sleep`](crate::transport::Sleep), the
+    /// error is [`JulesError::NoSleep`](crate::JulesError::NoSleep).
+    ///
+    /// # Arguments
+    ///
+    /// * `session_name` - The full resource name of the session.
+    /// * `poll_interval` - How long to wait between polls.
+    pub fn watch_session<'a>(
+        &'a self,
+        session_name: &'a str,
+        poll_interval: Duration,
+    ) -> Pin<Box<dyn Stream<Item = Result<WatchEvent>> + 'a>> {
+        let state = WatchState {
+            client: self,
+            session_name,
+            poll_interval,
+            seen: HashSet::new(),
+            state: None,
+            pending: VecDeque::new(),
+            polled: false,
+            finished: false,
+        };
+        Box::pin(futures_util::stream::unfold(
+            state,
+            |mut state| async move {
+                let item = state.next().await?;
+                Some((item, state))
+            },
+        ))
+    }
+}
+
+struct WatchState<'a> {
+    client: &'a JulesClient,
+    session_name: &'a str,
+    poll_interval: Duration,
+    seen: HashSet<String>,
+    state: Option<SessionState>,
+    pending: VecDeque<WatchEvent>,
+    polled: bool,
+    finished: bool,
+}
+
+impl WatchState<'_> {
+    async fn next(&mut self) -> Option<Result<WatchEvent>> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(Ok(event));
+            }
+            if self.finished {
+                return None;
+            }
+            if self.polled {
+                match self.client.sleeper() {
+                    Ok(sleep) => sleep.sleep(self.poll_interval).await,
+                    Err(e) => {
+                        self.finished = true;
+                        return Some(Err(e));
+                    }
+                }
+            }
+            self.polled = true;
+            if let Err(e) = self.poll().await {
+                self.finished = true;
+                return Some(Err(e));
+            }
+        }
+    }
+
+    async fn poll(&mut self) -> Result<()> {
+        // Read the state before the activities, so that once a terminal
+        // state is seen the activities leading up to it are included.
+        let session = self.client.get_session(self.session_name).await?;
+
+        let mut new_activities = Vec::new();
+        let mut activities = self.client.stream_activities(self.session_name);
+        while let Some(activity) = activities.next().await {
+            let activity = activity?;
+            if self.seen.insert(activity.name.clone()) {
+                new_activities.push(activity);
+            }
+        }
+        new_activities.sort_by_key(|a| a.create_time);
+        self.pending.extend(
+            new_activities
+                .into_iter()
+                .map(|a| WatchEvent::Activity(Box::new(a))),
+        );
+
+        if let Some(to) = session.state.clone() {
+            if self.state.as_ref() != Some(&to) {
+                #[cfg(feature = "tracing")]
+                tracing::info!(
+                    session = self.session_name,
+                    from = self.state.as_ref().map(SessionState::as_str),
+                    to = to.as_str(),
+                    "session state changed"
+                );
+                self.finished = to.is_terminal();
+                if self.finished {
+                    self.client.report_session(&session);
+                }
+                self.pending.push_back(WatchEvent::StateChanged {
+                    from: self.state.replace(to.clone()),
+                    to,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::JulesError;
+    use crate::transport::{HttpTransport, Request, Response, ThreadSleep};
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Answers with a session that is in progress for the first `polls`
+    /// polls and completed after that. An activity appears on the second
+    /// poll.
+    struct Progressing {
+        polls: usize,
+        sessions: AtomicUsize,
+    }
+
+    impl Progressing {
+        fn new(polls: usize) -> Self {
+            Self {
+                polls,
+                sessions: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl HttpTransport for Progressing {
+        async fn send(&self, request: Request) -> Result<Response> {
+            let polled = self.sessions.load(Ordering::SeqCst);
+            let body = if request.uri().path().ends_with("/activities") {
+                let activities = if polled >= 2 {
+                    serde_json::json!([{
+                        "name": "sessions/1/activities/a",
+                        "id": "a",
+                        "createTime": "2025-01-01T00:00:00Z",
+                        "originator": "agent",
+                    }])
+                } else {
+                    serde_json::json!([])
+                };
+                serde_json::json!({ "activities": activities })
+            } else {
+                let polled = self.sessions.fetch_add(1, Ordering::SeqCst);
+                let state = if polled < self.polls {
+                    "IN_PROGRESS"
+                } else {
+                    "COMPLETED"
+                };
+                serde_json::json!({
+                    "name": "sessions/1",
+                    "prompt": "p",
+                    "state": state,
+                    "sourceContext": { "source": "sources/s" },
+                })
+            };
+            Ok(Response::new(serde_json::to_vec(&body)?))
+        }
+    }
+
+    #[tokio::test]
+    async fn yields_activities_and_state_changes_until_terminal() {
+        let client = JulesClient::new("key")
+            .unwrap()
+            .with_transport(Progressing::new(3))
+            .with_sleep(ThreadSleep);
+        let events: Vec<_> = client
+            .watch_session("sessions/1", Duration::from_millis(1))
+            .map(|e| e.unwrap())
+            .collect()
+            .await;
+        assert_eq!(events.len(), 3, "{events:?}");
+        assert!(matches!(
+            &events[0],
+            WatchEvent::StateChanged {
+                from: None,
+                to: SessionState::InProgress
+            }
+        ));
+        assert!(matches!(&events[1], WatchEvent::Activity(a) if a.id == "a"));
+        assert!(matches!(
+            &events[2],
+            WatchEvent::StateChanged {
+                from: Some(SessionState::InProgress),
+                to: SessionState::Completed
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn fails_without_a_sleep() {
+        let mut client = JulesClient::new("key")
+            .unwrap()
+            .with_transport(Progressing::new(usize::MAX));
+        client.sleep = None;
+        let events: Vec<_> = client
+            .watch_session("sessions/1", Duration::from_millis(1))
+            .collect()
+            .await;
+        assert!(matches!(events[0], Ok(WatchEvent::StateChanged { .. })));
+        assert!(matches!(events[1], Err(JulesError::NoSleep)));
+        assert_eq!(events.len(), 2);
+    }
+}